@@ -1,19 +1,12 @@
-use std::env::current_dir;
-use std::fs::create_dir_all;
+use cosmwasm_schema::write_api;
 
-use cosmwasm_schema::{ export_schema, remove_schemas, schema_for };
-
-use injex_vesting::msg::{ ExecuteMsg, InstantiateMsg, QueryMsg };
-use injex_vesting::state::State;
+use injex_vesting::msg::{ ExecuteMsg, InstantiateMsg, QueryMsg, SudoMsg };
 
 fn main() {
-    let mut out_dir = current_dir().unwrap();
-    out_dir.push("schema");
-    create_dir_all(&out_dir).unwrap();
-    remove_schemas(&out_dir).unwrap();
-
-    export_schema(&schema_for!(InstantiateMsg), &out_dir);
-    export_schema(&schema_for!(ExecuteMsg), &out_dir);
-    export_schema(&schema_for!(QueryMsg), &out_dir);
-    export_schema(&schema_for!(State), &out_dir);
+    write_api! {
+        instantiate: InstantiateMsg,
+        execute: ExecuteMsg,
+        query: QueryMsg,
+        sudo: SudoMsg,
+    }
 }
@@ -0,0 +1,444 @@
+//! Prints the unlock table a set of grants would get from a contract instantiated with the
+//! given `InstantiateMsg`, using the contract's own vesting math.
+//!
+//! The grants file is a CSV with `address` and `amount` columns in its header, other columns are
+//! ignored so the `vest_batches` input can be reused. Period 0 is the instant claim at `--start`
+//! (unix seconds, 0 by default) and `dust` is the part of a grant that doesn't divide between
+//! the periods, it is never unlocked. The `total` rows aggregate every user.
+
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use anyhow::{ anyhow, bail, Context, Result };
+use cosmwasm_std::{ from_json, to_json_string, Timestamp, Uint256, Uint64 };
+use serde::Serialize;
+
+use injex_vesting::contract::{ calculate_reward_amount, split_vesting };
+use injex_vesting::msg::InstantiateMsg;
+use injex_vesting::state::PERCENTS;
+use injex_vesting::ContractError;
+
+const USAGE: &str =
+    "usage: simulate_schedule <instantiate.json> <grants.csv>
+    [--start <seconds>] [--format csv|json]";
+
+const TOTAL: &str = "total";
+
+#[derive(Serialize)]
+struct Schedule {
+    user: String,
+    amount: Uint256,
+    instant: Uint256,
+    per_period: Uint256,
+    dust: Uint256,
+    periods: Vec<Period>,
+}
+
+#[derive(Serialize)]
+struct Period {
+    period: u64,
+    unlock_time: u64,
+    amount: Uint256,
+    cumulative: Uint256,
+}
+
+#[derive(Serialize)]
+struct Report {
+    users: Vec<Schedule>,
+    total: Schedule,
+}
+
+fn main() -> Result<()> {
+    let mut paths = vec![];
+    let mut start = 0;
+    let mut json = false;
+
+    let mut args = std::env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--start" => {
+                let value = args.next().ok_or_else(|| anyhow!("--start needs a value"))?;
+                start = value.parse().context("--start")?;
+            }
+            "--format" =>
+                match args.next().as_deref() {
+                    Some("csv") => {
+                        json = false;
+                    }
+                    Some("json") => {
+                        json = true;
+                    }
+                    _ => bail!("--format expects csv or json"),
+                }
+            "-h" | "--help" => bail!("{USAGE}"),
+            _ if arg.starts_with("--") => bail!("unexpected argument {arg}\n{USAGE}"),
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+
+    let [msg_path, grants_path] = &paths[..] else {
+        bail!("{USAGE}");
+    };
+
+    let msg: InstantiateMsg = from_json(
+        fs::read(msg_path).with_context(|| format!("reading {}", msg_path.display()))?
+    ).with_context(|| format!("parsing {}", msg_path.display()))?;
+
+    // same checks as instantiate, lock_periods is only checked here to avoid dividing by zero
+    if msg.instant_claim_percents > PERCENTS {
+        bail!(ContractError::InvalidPercents {});
+    }
+
+    if msg.lock_minutes.is_zero() {
+        bail!(ContractError::InvalidLockMinutes {});
+    }
+
+    if msg.lock_periods.is_zero() {
+        bail!("lock_periods has to be positive");
+    }
+
+    let grants = parse_grants(
+        &fs
+            ::read_to_string(grants_path)
+            .with_context(|| format!("reading {}", grants_path.display()))?
+    )?;
+
+    let users = grants
+        .into_iter()
+        .map(|(user, amount)| -> Result<Schedule> {
+            simulate(&msg, user, amount, Timestamp::from_seconds(start))
+        })
+        .collect::<Result<Vec<Schedule>>>()?;
+
+    let report = Report { total: aggregate(&users), users };
+
+    if json {
+        println!("{}", to_json_string(&report)?);
+    } else {
+        println!("user,period,unlock_time,amount,cumulative");
+
+        for schedule in report.users.iter().chain([&report.total]) {
+            for period in &schedule.periods {
+                println!(
+                    "{},{},{},{},{}",
+                    schedule.user,
+                    period.period,
+                    period.unlock_time,
+                    period.amount,
+                    period.cumulative
+                );
+            }
+
+            println!("{},dust,,{},", schedule.user, schedule.dust);
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_grants(csv: &str) -> Result<Vec<(String, Uint256)>> {
+    let mut rows = csv
+        .lines()
+        .enumerate()
+        .map(|(index, line)| -> (usize, &str) { (index + 1, line.trim()) })
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+    let header: Vec<&str> = rows
+        .next()
+        .map(|(_, line)| -> Vec<&str> { line.split(',').map(str::trim).collect() })
+        .unwrap_or_default();
+
+    let column = |name: &str| -> Result<usize> {
+        header
+            .iter()
+            .position(|column| *column == name)
+            .ok_or_else(|| anyhow!("the grants header has no {name} column"))
+    };
+
+    let (address_column, amount_column) = (column("address")?, column("amount")?);
+
+    let mut grants: Vec<(String, Uint256)> = vec![];
+
+    for (line, row) in rows {
+        let fields: Vec<&str> = row.split(',').map(str::trim).collect();
+
+        if fields.len() != header.len() {
+            bail!("line {line}: expected {} fields, got {}", header.len(), fields.len());
+        }
+
+        let address = fields[address_column];
+        let amount = Uint256::from_str(fields[amount_column]).map_err(|err| {
+            anyhow!("line {line}: {err}")
+        })?;
+
+        if amount.is_zero() {
+            bail!("line {line}: {}", ContractError::InvalidInjxAmount {});
+        }
+
+        // vesting a user again replaces the grant, so a repeated address is a mistake
+        if address == TOTAL || grants.iter().any(|(user, _)| user == address) {
+            bail!("line {line}: {address} is listed twice");
+        }
+
+        grants.push((address.to_string(), amount));
+    }
+
+    Ok(grants)
+}
+
+fn simulate(
+    msg: &InstantiateMsg,
+    user: String,
+    amount: Uint256,
+    start: Timestamp
+) -> Result<Schedule> {
+    let lock_minutes = Uint64::try_from(msg.lock_minutes)?.u64();
+    let lock_periods = Uint64::try_from(msg.lock_periods)?.u64();
+
+    let (instant, per_period) = split_vesting(
+        amount,
+        msg.instant_claim_percents,
+        msg.lock_periods
+    );
+    let total_periodic = per_period * msg.lock_periods;
+
+    let mut periods = vec![Period {
+        period: 0,
+        unlock_time: start.seconds(),
+        amount: instant,
+        cumulative: instant,
+    }];
+    let mut unlocked = Uint256::zero();
+
+    for period in 1..=lock_periods {
+        let time = start.plus_minutes(lock_minutes * period);
+        let vested = calculate_reward_amount(
+            per_period,
+            time.seconds(),
+            start.seconds(),
+            0,
+            lock_minutes,
+            total_periodic
+        )?;
+
+        periods.push(Period {
+            period,
+            unlock_time: time.seconds(),
+            amount: vested - unlocked,
+            cumulative: instant + vested,
+        });
+
+        unlocked = vested;
+    }
+
+    Ok(Schedule {
+        user,
+        amount,
+        instant,
+        per_period,
+        dust: amount - instant - total_periodic,
+        periods,
+    })
+}
+
+fn aggregate(users: &[Schedule]) -> Schedule {
+    let mut total = Schedule {
+        user: TOTAL.to_string(),
+        amount: Uint256::zero(),
+        instant: Uint256::zero(),
+        per_period: Uint256::zero(),
+        dust: Uint256::zero(),
+        periods: vec![],
+    };
+
+    for schedule in users {
+        total.amount += schedule.amount;
+        total.instant += schedule.instant;
+        total.per_period += schedule.per_period;
+        total.dust += schedule.dust;
+
+        for (index, period) in schedule.periods.iter().enumerate() {
+            match total.periods.get_mut(index) {
+                Some(sum) => {
+                    sum.amount += period.amount;
+                    sum.cumulative += period.cumulative;
+                }
+                None =>
+                    total.periods.push(Period {
+                        period: period.period,
+                        unlock_time: period.unlock_time,
+                        amount: period.amount,
+                        cumulative: period.cumulative,
+                    }),
+            }
+        }
+    }
+
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instantiate_msg(
+        instant_claim_percents: u128,
+        lock_minutes: u128,
+        lock_periods: u128
+    ) -> InstantiateMsg {
+        InstantiateMsg {
+            instant_claim_percents: Uint256::from_u128(instant_claim_percents),
+            lock_minutes: Uint256::from_u128(lock_minutes),
+            lock_periods: Uint256::from_u128(lock_periods),
+            injex_token: "inj".to_string(),
+            admin: "admin".to_string(),
+            voucher_signer: None,
+            extra_denoms: vec![],
+            max_total_allocation: None,
+            treasury: None,
+            mint_cap: None,
+            early_unlock: None,
+            reward_denoms: vec![],
+            min_delay: 3600,
+            approvals: None,
+            dao: None,
+        }
+    }
+
+    fn rows(schedule: &Schedule) -> Vec<(u64, u64, u128, u128)> {
+        schedule.periods
+            .iter()
+            .map(|period| -> (u64, u64, u128, u128) {
+                (
+                    period.period,
+                    period.unlock_time,
+                    period.amount.to_string().parse().unwrap(),
+                    period.cumulative.to_string().parse().unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    fn simulate_user(msg: &InstantiateMsg, amount: u128, start: Timestamp) -> Schedule {
+        simulate(msg, format!("user{amount}"), Uint256::from_u128(amount), start).unwrap()
+    }
+
+    #[test]
+    fn simulates_periods() {
+        let msg = instantiate_msg(1500, 5, 3);
+        let start = Timestamp::from_seconds(1_000);
+
+        let schedule = simulate_user(&msg, 1_000_000, start);
+
+        assert_eq!(schedule.instant, Uint256::from_u128(150_000));
+        assert_eq!(schedule.per_period, Uint256::from_u128(283_333));
+        assert_eq!(schedule.dust, Uint256::from_u128(1));
+        assert_eq!(
+            rows(&schedule),
+            vec![
+                (0, 1_000, 150_000, 150_000),
+                (1, 1_300, 283_333, 433_333),
+                (2, 1_600, 283_333, 716_666),
+                (3, 1_900, 283_333, 999_999)
+            ]
+        );
+
+        // the dust grows with what doesn't divide between the periods
+        let schedule = simulate_user(&msg, 1_001, start);
+
+        assert_eq!(schedule.instant, Uint256::from_u128(150));
+        assert_eq!(schedule.per_period, Uint256::from_u128(283));
+        assert_eq!(schedule.dust, Uint256::from_u128(2));
+        assert_eq!(schedule.periods[3].cumulative, Uint256::from_u128(999));
+    }
+
+    #[test]
+    fn matches_the_contract_math() {
+        let msg = instantiate_msg(1234, 60, 12);
+        let start = Timestamp::from_seconds(1_700_000_000);
+        let amount = Uint256::from_u128(987_654_321);
+
+        let schedule = simulate(&msg, "user".to_string(), amount, start).unwrap();
+
+        let (instant, per_period) = split_vesting(
+            amount,
+            msg.instant_claim_percents,
+            msg.lock_periods
+        );
+        let total_periodic = per_period * msg.lock_periods;
+
+        let vested = |time: u64| -> Uint256 {
+            calculate_reward_amount(per_period, time, start.seconds(), 0, 60, total_periodic)
+                .unwrap()
+        };
+
+        assert_eq!((schedule.instant, schedule.per_period), (instant, per_period));
+        assert_eq!(schedule.dust, amount - instant - total_periodic);
+        assert_eq!(schedule.periods.len(), 13);
+
+        // every period releases what the contract unlocks between a second before and at it
+        for period in &schedule.periods[1..] {
+            assert_eq!(period.amount, vested(period.unlock_time) - vested(period.unlock_time - 1));
+            assert_eq!(period.cumulative, instant + vested(period.unlock_time));
+        }
+
+        let unlocked = schedule.periods
+            .iter()
+            .fold(Uint256::zero(), |sum, period| -> Uint256 { sum + period.amount });
+
+        assert_eq!(unlocked + schedule.dust, amount);
+        assert_eq!(schedule.periods[12].cumulative, unlocked);
+    }
+
+    #[test]
+    fn aggregates_users() {
+        let msg = instantiate_msg(1500, 5, 3);
+        let start = Timestamp::from_seconds(1_000);
+
+        let users = vec![simulate_user(&msg, 1_000_000, start), simulate_user(&msg, 1_001, start)];
+
+        let total = aggregate(&users);
+
+        assert_eq!(total.user, TOTAL);
+        assert_eq!(total.amount, Uint256::from_u128(1_001_001));
+        assert_eq!(total.instant, Uint256::from_u128(150_150));
+        assert_eq!(total.per_period, Uint256::from_u128(283_616));
+        assert_eq!(total.dust, Uint256::from_u128(3));
+        assert_eq!(
+            rows(&total),
+            vec![
+                (0, 1_000, 150_150, 150_150),
+                (1, 1_300, 283_616, 433_766),
+                (2, 1_600, 283_616, 717_382),
+                (3, 1_900, 283_616, 1_000_998)
+            ]
+        );
+
+        assert!(aggregate(&[]).periods.is_empty());
+    }
+
+    #[test]
+    fn parses_grants() {
+        let grants = parse_grants(
+            "# vest_batches input\naddress,amount,schedule\ninj1a,100,seed\n\ninj1b,200,team\n"
+        ).unwrap();
+
+        assert_eq!(
+            grants,
+            vec![
+                ("inj1a".to_string(), Uint256::from_u128(100)),
+                ("inj1b".to_string(), Uint256::from_u128(200))
+            ]
+        );
+
+        let error = |csv: &str| -> String { parse_grants(csv).unwrap_err().to_string() };
+
+        assert_eq!(error("user,amount\ninj1a,1"), "the grants header has no address column");
+        assert_eq!(error("address,amount\ninj1a,1\ninj1a,2"), "line 3: inj1a is listed twice");
+        assert_eq!(error("address,amount\ntotal,1"), "line 2: total is listed twice");
+        assert_eq!(error("address,amount\ninj1a,0"), "line 2: Invalid user INJX amount");
+        assert_eq!(error("address,amount\ninj1a"), "line 2: expected 2 fields, got 1");
+    }
+}
@@ -0,0 +1,457 @@
+//! Turns an allocation CSV into ready-to-broadcast `VestTokens` execute messages.
+//!
+//! The CSV needs an `address,amount,schedule` header. `amount` is in base units of the vested
+//! token and `schedule` names the vesting instance the row belongs to, every instance has its
+//! own lock schedule and is mapped to a contract address with `--contract`. Quoted fields are
+//! not supported, empty lines and lines starting with `#` are skipped.
+//!
+//! Every batch is written to `<out>/<schedule>-<n>.json` as `{ contract, msg, funds }`, where
+//! `funds` is exactly the sum of the batch. Nothing is sent to a chain.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use anyhow::{ anyhow, bail, Context, Result };
+use bech32::{ FromBase32, Variant };
+use cosmwasm_std::{ to_json_vec, Coin, Uint128, Uint256 };
+use serde::Serialize;
+
+use injex_vesting::msg::{ ExecuteMsg, UserVesting };
+
+const USAGE: &str =
+    "usage: vest_batches <allocations.csv> --denom <denom> --contract <schedule>=<address>...
+    [--out <dir>] [--gas-budget <gas>] [--base-gas <gas>] [--gas-per-user <gas>]
+    [--max-users <n>] [--mint]";
+
+const ADDRESS_PREFIX: &str = "inj";
+
+struct Args {
+    input: PathBuf,
+    out: PathBuf,
+    denom: String,
+    contracts: BTreeMap<String, String>,
+    gas_budget: u64,
+    base_gas: u64,
+    gas_per_user: u64,
+    max_users: Option<usize>,
+    // grants of a minting contract are not funded
+    mint: bool,
+}
+
+struct Allocation {
+    line: usize,
+    address: String,
+    amount: Uint256,
+}
+
+#[derive(Serialize)]
+struct Batch {
+    contract: String,
+    msg: ExecuteMsg,
+    funds: Vec<Coin>,
+}
+
+fn main() -> Result<()> {
+    let args = parse_args(std::env::args().skip(1))?;
+
+    let csv = fs
+        ::read_to_string(&args.input)
+        .with_context(|| format!("reading {}", args.input.display()))?;
+    let schedules = parse_allocations(&csv)?;
+
+    for schedule in schedules.keys() {
+        if !args.contracts.contains_key(schedule) {
+            bail!("no contract for schedule {schedule}, pass --contract {schedule}=<address>");
+        }
+    }
+
+    let batch_size = batch_size(&args)?;
+
+    fs::create_dir_all(&args.out).with_context(|| format!("creating {}", args.out.display()))?;
+
+    let mut grand_total = Uint256::zero();
+    let mut users = 0;
+
+    for (schedule, allocations) in &schedules {
+        let mut total = Uint256::zero();
+
+        for (index, chunk) in allocations.chunks(batch_size).enumerate() {
+            let batch = build_batch(&args, &args.contracts[schedule], chunk)?;
+            let path = args.out.join(format!("{schedule}-{}.json", index + 1));
+
+            fs::write(&path, to_json_vec(&batch)?).with_context(||
+                format!("writing {}", path.display())
+            )?;
+
+            total += chunk
+                .iter()
+                .fold(Uint256::zero(), |sum, allocation| -> Uint256 { sum + allocation.amount });
+        }
+
+        println!(
+            "{schedule}: {} users, {total} {}, {} batches",
+            allocations.len(),
+            args.denom,
+            allocations.len().div_ceil(batch_size)
+        );
+
+        grand_total += total;
+        users += allocations.len();
+    }
+
+    println!("total: {users} users, {grand_total} {}", args.denom);
+
+    Ok(())
+}
+
+fn parse_args(mut raw: impl Iterator<Item = String>) -> Result<Args> {
+    let mut input = None;
+    let mut args = Args {
+        input: PathBuf::new(),
+        out: PathBuf::from("batches"),
+        denom: String::new(),
+        contracts: BTreeMap::new(),
+        gas_budget: 4_000_000,
+        base_gas: 200_000,
+        gas_per_user: 40_000,
+        max_users: None,
+        mint: false,
+    };
+
+    while let Some(arg) = raw.next() {
+        let mut value = || -> Result<String> {
+            raw.next().ok_or_else(|| anyhow!("{arg} needs a value\n{USAGE}"))
+        };
+
+        match arg.as_str() {
+            "--out" => {
+                args.out = PathBuf::from(value()?);
+            }
+            "--denom" => {
+                args.denom = value()?;
+            }
+            "--contract" => {
+                let value = value()?;
+                let (schedule, contract) = value
+                    .split_once('=')
+                    .ok_or_else(|| anyhow!("--contract expects <schedule>=<address>"))?;
+
+                validate_address(contract).with_context(|| format!("contract of {schedule}"))?;
+                args.contracts.insert(schedule.to_string(), contract.to_string());
+            }
+            "--gas-budget" => {
+                args.gas_budget = value()?.parse().context("--gas-budget")?;
+            }
+            "--base-gas" => {
+                args.base_gas = value()?.parse().context("--base-gas")?;
+            }
+            "--gas-per-user" => {
+                args.gas_per_user = value()?.parse().context("--gas-per-user")?;
+            }
+            "--max-users" => {
+                args.max_users = Some(value()?.parse().context("--max-users")?);
+            }
+            "--mint" => {
+                args.mint = true;
+            }
+            "-h" | "--help" => bail!("{USAGE}"),
+            _ if arg.starts_with("--") || input.is_some() => {
+                bail!("unexpected argument {arg}\n{USAGE}")
+            }
+            _ => {
+                input = Some(PathBuf::from(arg));
+            }
+        }
+    }
+
+    args.input = input.ok_or_else(|| anyhow!("{USAGE}"))?;
+
+    if args.denom.is_empty() {
+        bail!("--denom is required\n{USAGE}");
+    }
+
+    Ok(args)
+}
+
+/// Users per batch so that a batch stays within the gas budget and the message limit
+fn batch_size(args: &Args) -> Result<usize> {
+    if args.gas_per_user == 0 {
+        bail!("--gas-per-user has to be positive");
+    }
+
+    let by_gas = (args.gas_budget.saturating_sub(args.base_gas) / args.gas_per_user) as usize;
+    let size = args.max_users.map_or(by_gas, |max_users| -> usize { max_users.min(by_gas) });
+
+    if size == 0 {
+        bail!("the gas budget does not fit a single user");
+    }
+
+    Ok(size)
+}
+
+/// Rows grouped by schedule in file order
+fn parse_allocations(csv: &str) -> Result<BTreeMap<String, Vec<Allocation>>> {
+    let mut rows = csv
+        .lines()
+        .enumerate()
+        .map(|(index, line)| -> (usize, &str) { (index + 1, line.trim()) })
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+    let header: Vec<&str> = rows
+        .next()
+        .map(|(_, line)| -> Vec<&str> { line.split(',').map(str::trim).collect() })
+        .unwrap_or_default();
+
+    if header != ["address", "amount", "schedule"] {
+        bail!("expected an address,amount,schedule header");
+    }
+
+    let mut schedules: BTreeMap<String, Vec<Allocation>> = BTreeMap::new();
+    let mut seen: BTreeMap<(String, String), usize> = BTreeMap::new();
+
+    for (line, row) in rows {
+        let fields: Vec<&str> = row.split(',').map(str::trim).collect();
+
+        let [address, amount, schedule] = fields[..] else {
+            bail!("line {line}: expected 3 fields, got {}", fields.len());
+        };
+
+        validate_address(address).with_context(|| format!("line {line}"))?;
+
+        let amount = Uint256::from_str(amount).map_err(|err| anyhow!("line {line}: {err}"))?;
+
+        if amount.is_zero() {
+            bail!("line {line}: amount has to be positive");
+        }
+
+        if schedule.is_empty() {
+            bail!("line {line}: schedule is empty");
+        }
+
+        if let Some(first) = seen.insert((schedule.to_string(), address.to_string()), line) {
+            bail!("line {line}: {address} is already allocated in {schedule} on line {first}");
+        }
+
+        schedules.entry(schedule.to_string()).or_default().push(Allocation {
+            line,
+            address: address.to_string(),
+            amount,
+        });
+    }
+
+    if schedules.is_empty() {
+        bail!("no allocations");
+    }
+
+    Ok(schedules)
+}
+
+fn validate_address(address: &str) -> Result<()> {
+    // the contract only accepts the normalized form
+    if address.to_lowercase() != address {
+        bail!("{address} is not lowercase");
+    }
+
+    let (prefix, data, variant) = bech32
+        ::decode(address)
+        .map_err(|err| anyhow!("{address} is not a bech32 address: {err}"))?;
+
+    if prefix != ADDRESS_PREFIX || variant != Variant::Bech32 {
+        bail!("{address} is not an {ADDRESS_PREFIX} address");
+    }
+
+    let bytes = Vec::<u8>::from_base32(&data).map_err(|err| anyhow!("{address}: {err}"))?;
+
+    if bytes.len() != 20 && bytes.len() != 32 {
+        bail!("{address} has an invalid length");
+    }
+
+    Ok(())
+}
+
+fn build_batch(args: &Args, contract: &str, allocations: &[Allocation]) -> Result<Batch> {
+    let total = allocations
+        .iter()
+        .fold(Uint256::zero(), |sum, allocation| -> Uint256 { sum + allocation.amount });
+
+    let funds = if args.mint {
+        vec![]
+    } else {
+        let last_line = allocations[allocations.len() - 1].line;
+        let amount = Uint128::try_from(total).map_err(|_| {
+            anyhow!("batch ending on line {last_line} does not fit into a coin")
+        })?;
+
+        vec![Coin { denom: args.denom.clone(), amount }]
+    };
+
+    Ok(Batch {
+        contract: contract.to_string(),
+        msg: ExecuteMsg::VestTokens {
+            users: allocations
+                .iter()
+                .map(|allocation| -> UserVesting {
+                    UserVesting {
+                        amount: allocation.amount,
+                        user: allocation.address.clone(),
+                        extra: vec![],
+                    }
+                })
+                .collect(),
+        },
+        funds,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use bech32::ToBase32;
+
+    use super::*;
+
+    fn address(prefix: &str, len: usize, variant: Variant) -> String {
+        bech32::encode(prefix, vec![7_u8; len].to_base32(), variant).unwrap()
+    }
+
+    fn args(extra: &[&str]) -> Args {
+        let raw = ["allocations.csv", "--denom", "inj"].iter().chain(extra);
+
+        parse_args(raw.map(|arg| -> String { arg.to_string() })).unwrap()
+    }
+
+    #[test]
+    fn validates_addresses() {
+        assert!(validate_address(&address("inj", 20, Variant::Bech32)).is_ok());
+        assert!(validate_address(&address("inj", 32, Variant::Bech32)).is_ok());
+
+        assert!(validate_address(&address("cosmos", 20, Variant::Bech32)).is_err());
+        assert!(validate_address(&address("inj", 20, Variant::Bech32m)).is_err());
+        assert!(validate_address(&address("inj", 19, Variant::Bech32)).is_err());
+        assert!(validate_address(&address("inj", 33, Variant::Bech32)).is_err());
+        assert!(validate_address(&address("inj", 20, Variant::Bech32).to_uppercase()).is_err());
+        assert!(validate_address("inj1notbech32").is_err());
+    }
+
+    #[test]
+    fn parses_allocations() {
+        let first = address("inj", 20, Variant::Bech32);
+        let second = address("inj", 32, Variant::Bech32);
+
+        let csv = format!(
+            "# seed round\naddress,amount,schedule\n\n{first},100,seed\n{second},200,seed\n\
+            {first},300,team\n"
+        );
+        let schedules = parse_allocations(&csv).unwrap();
+
+        assert_eq!(schedules.keys().collect::<Vec<&String>>(), ["seed", "team"]);
+
+        let seed = &schedules["seed"];
+        assert_eq!(seed.len(), 2);
+        assert_eq!((seed[0].line, seed[0].address.as_str()), (4, first.as_str()));
+        assert_eq!(seed[0].amount, Uint256::from_u128(100_u128));
+        assert_eq!((seed[1].line, seed[1].amount), (5, Uint256::from_u128(200_u128)));
+        assert_eq!(schedules["team"][0].amount, Uint256::from_u128(300_u128));
+    }
+
+    #[test]
+    fn rejects_invalid_allocations() {
+        let user = address("inj", 20, Variant::Bech32);
+
+        let error = |csv: String| -> String {
+            format!("{:#}", parse_allocations(&csv).err().unwrap())
+        };
+
+        assert_eq!(
+            error(format!("amount,address,schedule\n{user},1,seed")),
+            "expected an address,amount,schedule header"
+        );
+        assert_eq!(error("address,amount,schedule\n".to_string()), "no allocations");
+        assert_eq!(
+            error(format!("address,amount,schedule\n{user},1,seed\n{user},2,seed")),
+            format!("line 3: {user} is already allocated in seed on line 2")
+        );
+        assert_eq!(
+            error(format!("address,amount,schedule\n{user},0,seed")),
+            "line 2: amount has to be positive"
+        );
+        assert_eq!(
+            error(format!("address,amount,schedule\n{user},1")),
+            "line 2: expected 3 fields, got 2"
+        );
+        assert_eq!(
+            error(format!("address,amount,schedule\n{user},1,")),
+            "line 2: schedule is empty"
+        );
+        assert!(
+            error("address,amount,schedule\ncosmos1x,1,seed".to_string()).starts_with("line 2: ")
+        );
+    }
+
+    #[test]
+    fn sizes_batches() {
+        // (4_000_000 - 200_000) / 40_000
+        assert_eq!(batch_size(&args(&[])).unwrap(), 95);
+        assert_eq!(batch_size(&args(&["--max-users", "50"])).unwrap(), 50);
+        assert_eq!(batch_size(&args(&["--max-users", "500"])).unwrap(), 95);
+        assert_eq!(
+            batch_size(&args(&["--gas-budget", "280000", "--base-gas", "200000"])).unwrap(),
+            2
+        );
+
+        assert!(batch_size(&args(&["--gas-per-user", "0"])).is_err());
+        assert!(batch_size(&args(&["--gas-budget", "200000"])).is_err());
+        assert!(batch_size(&args(&["--max-users", "0"])).is_err());
+    }
+
+    #[test]
+    fn builds_batches() {
+        let allocations = [100_u128, 250, 650]
+            .iter()
+            .enumerate()
+            .map(|(index, amount)| -> Allocation {
+                Allocation {
+                    line: index + 2,
+                    address: address("inj", 20 + index, Variant::Bech32),
+                    amount: Uint256::from_u128(*amount),
+                }
+            })
+            .collect::<Vec<Allocation>>();
+
+        let batch = build_batch(&args(&[]), "inj1contract", &allocations).unwrap();
+
+        assert_eq!(batch.contract, "inj1contract");
+        assert_eq!(
+            batch.funds,
+            vec![Coin {
+                denom: "inj".to_string(),
+                amount: Uint128::new(1_000),
+            }]
+        );
+
+        let ExecuteMsg::VestTokens { users } = batch.msg else {
+            panic!("expected VestTokens");
+        };
+
+        assert_eq!(users.len(), 3);
+        assert_eq!(users[1].user, allocations[1].address);
+        assert_eq!(users[1].amount, Uint256::from_u128(250_u128));
+        assert!(users.iter().all(|user| user.extra.is_empty()));
+
+        // grants of a minting contract are not funded
+        let minted = build_batch(&args(&["--mint"]), "inj1contract", &allocations).unwrap();
+        assert_eq!(minted.funds, vec![]);
+
+        let too_large = [Allocation {
+            line: 7,
+            address: allocations[0].address.clone(),
+            amount: Uint256::from_uint128(Uint128::MAX) + Uint256::one(),
+        }];
+
+        assert_eq!(
+            build_batch(&args(&[]), "inj1contract", &too_large).err().unwrap().to_string(),
+            "batch ending on line 7 does not fit into a coin"
+        );
+    }
+}
@@ -1,52 +1,137 @@
+use std::collections::BTreeMap;
 use std::str::FromStr;
 
-#[cfg(not(feature = "library"))]
+#[cfg(not(any(feature = "library", feature = "factory")))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
     to_json_binary,
+    to_json_string,
+    to_json_vec,
     Addr,
+    Api,
     BankMsg,
     Binary,
+    BlockInfo,
     Coin,
     CosmosMsg,
+    Decimal256,
     Deps,
     DepsMut,
+    Empty,
     Env,
+    Event,
     MessageInfo,
+    Order,
     Response,
     StdError,
     StdResult,
+    Storage,
     Timestamp,
     Uint128,
     Uint256,
+    Uint64,
+    WasmMsg,
 };
-use cw2::set_contract_version;
+use cw2::{ get_contract_version, set_contract_version };
+use cw_storage_plus::Bound;
+use cw721::OwnerOfResponse;
+use sha2::Digest;
 
 use crate::error::ContractError;
-use crate::msg::{ ExecuteMsg, InstantiateMsg, QueryMsg, UserVesting, UserVestingInfo };
+use crate::injective::{ create_mint_tokens_msg, InjectiveMsgWrapper };
+use crate::msg::{
+    AuditPositionsResponse,
+    ClaimHistoryResponse,
+    ConfigResponse,
+    DenomResponse,
+    EarlyUnlockQuote,
+    ApprovalsMsg,
+    ExecuteMsg,
+    GrantResponse,
+    InfoResponse,
+    InstantiateMsg,
+    PendingConfigChangesResponse,
+    PositionAudit,
+    RewardsResponse,
+    PositionIssue,
+    ProposalsResponse,
+    QueryMsg,
+    SimulateAtResponse,
+    SudoMsg,
+    SolvencyResponse,
+    StatsResponse,
+    TotalPowerAtHeightResponse,
+    TotalsResponse,
+    UnallocatedResponse,
+    UnlockScheduleResponse,
+    UnlockTranche,
+    UserVesting,
+    UserVestingInfo,
+    Voucher,
+    VotingPowerAtHeightResponse,
+};
 use crate::state::{
+    ApprovalConfig,
+    ClaimRecord,
     Config,
+    ConfigChange,
+    PendingConfigChange,
+    Proposal,
+    CONFIG_CHANGE_COUNT,
+    DAO,
+    PROPOSALS,
+    PROPOSAL_COUNT,
+    PENDING_CONFIG_CHANGES,
+    DenomClaims,
+    EarlyUnlockConfig,
+    PenaltyDestination,
+    RewardCheckpoint,
+    RewardTotals,
+    REWARD_CHECKPOINTS,
+    REWARD_INDEX_HISTORY,
+    BONUS_CHECKPOINTS,
+    BONUS_INDEX_HISTORY,
+    CLAIM_COUNT,
+    CLAIM_HISTORY,
+    MerkleAllocation,
+    GRANTS,
+    GRANT_COUNT,
+    GRANT_IDS,
     State,
     UserClaims,
     ADMIN,
     CLAIMABLE_AMOUNT,
     CONFIG,
+    EXTRA_CLAIMS,
     INSTANT_CLAIMABLE_AMOUNT,
+    MERKLE_ALLOCATION,
+    MERKLE_REGISTERED,
+    PAUSED,
     PERCENTS,
     STATE,
+    TOTAL_POWER,
+    USED_VOUCHER_NONCES,
+    VESTING_COHORTS,
+    UNLOCK_SCHEDULE,
+    VOTING_POWER,
+    VoucherSigner,
 };
 
 // version info for migration info
 const CONTRACT_NAME: &str = "injex-vesting";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
-#[cfg_attr(not(feature = "library"), entry_point)]
+// pagination
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+#[cfg_attr(not(any(feature = "library", feature = "factory")), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: InstantiateMsg
-) -> Result<Response, ContractError> {
+) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
     if msg.instant_claim_percents > PERCENTS {
         return Err(ContractError::InvalidPercents {});
     }
@@ -55,393 +140,2944 @@ pub fn instantiate(
         return Err(ContractError::InvalidLockMinutes {});
     }
 
+    if let Some(signer) = &msg.voucher_signer {
+        validate_voucher_signer(signer)?;
+    }
+
+    validate_extra_denoms(&msg.injex_token, &msg.extra_denoms)?;
+
+    if let Some(early_unlock) = &msg.early_unlock {
+        validate_early_unlock(early_unlock)?;
+    }
+
+    validate_reward_denoms(&msg.reward_denoms)?;
+
+    if msg.min_delay == 0 {
+        return Err(ContractError::InvalidMinDelay {});
+    }
+
+    let approvals = msg.approvals
+        .map(|approvals| validate_approvals(deps.api, approvals))
+        .transpose()?;
+
+    let treasury = msg.treasury.map(|treasury| deps.api.addr_validate(&treasury)).transpose()?;
+
     let config = Config {
         injex_token: msg.injex_token,
         instant_claim_percents: msg.instant_claim_percents,
         lock_minutes: msg.lock_minutes,
         lock_periods: msg.lock_periods,
+        voucher_signer: msg.voucher_signer,
+        grant_nft: None,
+        extra_denoms: msg.extra_denoms,
+        max_total_allocation: msg.max_total_allocation,
+        treasury,
+        mint_cap: msg.mint_cap,
+        early_unlock: msg.early_unlock,
+        reward_denoms: msg.reward_denoms,
+        min_delay: msg.min_delay,
+        approvals,
     };
 
     let state = State {
         total_claimed: Uint256::zero(),
         total_vested: Uint256::zero(),
+        beneficiary_count: 0,
+        fully_claimed_count: 0,
+        instant_outstanding: Uint256::zero(),
+        periodic_claimed: Uint256::zero(),
+        extra_totals: vec![],
+        periodic_offset_plus: Uint256::zero(),
+        periodic_offset_minus: Uint256::zero(),
+        total_minted: Uint256::zero(),
+        bonus_index: Decimal256::zero(),
+        bonus_outstanding: Uint256::zero(),
+        reward_totals: vec![],
+        total_locked: Uint256::zero(),
     };
 
-    let admin = msg.admin;
+    let admin = deps.api.addr_validate(&msg.admin)?;
+    let dao = match msg.dao {
+        Some(dao) => deps.api.addr_validate(&dao)?,
+        None => info.sender.clone(),
+    };
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
     CONFIG.save(deps.storage, &config)?;
-    ADMIN.save(deps.storage, &deps.api.addr_validate(&admin)?)?;
+    ADMIN.save(deps.storage, &admin)?;
+    DAO.save(deps.storage, &dao)?;
     STATE.save(deps.storage, &state)?;
+    TOTAL_POWER.save(deps.storage, &Uint128::zero(), env.block.height)?;
 
-    Ok(Response::new().add_attribute("method", "instantiate").add_attribute("owner", info.sender))
+    Ok(
+        Response::new()
+            .add_attribute("method", "instantiate")
+            .add_attribute("owner", info.sender)
+            .add_attribute("admin", admin)
+            .add_attribute("dao", dao)
+            .add_attribute("injex_token", config.injex_token)
+            .add_attribute("instant_claim_percents", config.instant_claim_percents)
+            .add_attribute("lock_minutes", config.lock_minutes)
+            .add_attribute("lock_periods", config.lock_periods)
+            .add_attribute("voucher_signer", voucher_signer_value(&config.voucher_signer)?)
+            .add_attribute("extra_denoms", config.extra_denoms.join(","))
+            .add_attribute(
+                "max_total_allocation",
+                config.max_total_allocation.map(|cap| cap.to_string()).unwrap_or_default()
+            )
+            .add_attribute("treasury", config.treasury.map(String::from).unwrap_or_default())
+            .add_attribute(
+                "mint_cap",
+                config.mint_cap.map(|cap| cap.to_string()).unwrap_or_default()
+            )
+            .add_attribute("early_unlock", early_unlock_value(&config.early_unlock)?)
+            .add_attribute("reward_denoms", config.reward_denoms.join(","))
+            .add_attribute("min_delay", config.min_delay.to_string())
+            .add_attribute("approvals", approvals_value(&config.approvals)?)
+    )
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
+#[cfg_attr(not(any(feature = "library", feature = "factory")), entry_point)]
 pub fn execute(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
     msg: ExecuteMsg
-) -> Result<Response, ContractError> {
+) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
+    if PAUSED.may_load(deps.storage)?.unwrap_or_default() {
+        return Err(ContractError::Paused {});
+    }
+
+    if CONFIG.load(deps.storage)?.approvals.is_some() && is_privileged(&msg) {
+        return Err(ContractError::ApprovalRequired {});
+    }
+
+    dispatch(deps, _env, info, msg)
+}
+
+// Routes a message that passed the pause and approval checks of `execute`
+fn dispatch(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg
+) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
     match msg {
         ExecuteMsg::VestTokens { users } => vest_tokens(users, deps, _env, info),
         ExecuteMsg::Claim {} => claim(deps, _env, info),
         ExecuteMsg::ChangeAdmin { new_admin } => change_admin(deps, info, new_admin),
-        ExecuteMsg::ChangeLockMinutes { new_lock_minutes } =>
-            change_lock_minutes(deps, info, new_lock_minutes),
-        ExecuteMsg::ChangeInstantClaimPercents { new_percents } =>
-            change_instant_percents(deps, info, new_percents),
+        ExecuteMsg::ChangeLockMinutes { .. } | ExecuteMsg::ChangeInstantClaimPercents { .. } =>
+            change_timelocked(deps.as_ref(), info),
+        ExecuteMsg::RegisterMerkleRoot { merkle_root, total_amount } =>
+            register_merkle_root(deps, _env, info, merkle_root, total_amount),
+        ExecuteMsg::RegisterVesting { amount, vesting_start, proof } =>
+            register_vesting(deps, _env, info, amount, vesting_start, proof),
+        ExecuteMsg::ChangeVoucherSigner { new_signer } =>
+            change_voucher_signer(deps, info, new_signer),
+        ExecuteMsg::RedeemVoucher { voucher, signature } =>
+            redeem_voucher(deps, _env, info, voucher, signature),
+        ExecuteMsg::SetGrantNft { nft_contract } => set_grant_nft(deps, info, nft_contract),
+        ExecuteMsg::ClaimGrant { grant_id } => claim_grant(deps, _env, info, grant_id),
+        ExecuteMsg::SetExtraDenoms { denoms } => set_extra_denoms(deps, info, denoms),
+        ExecuteMsg::Deposit {} => deposit(deps, _env, info),
+        ExecuteMsg::SetMaxTotalAllocation { max_total_allocation } =>
+            set_max_total_allocation(deps, info, max_total_allocation),
+        ExecuteMsg::ChangeTreasury { new_treasury } => change_treasury(deps, info, new_treasury),
+        ExecuteMsg::IncreaseGrant { user, grant_id, amount } =>
+            amend_grant(deps, _env, info, user, grant_id, amount, true),
+        ExecuteMsg::DecreaseGrant { user, grant_id, amount } =>
+            amend_grant(deps, _env, info, user, grant_id, amount, false),
+        ExecuteMsg::SetEarlyUnlock { early_unlock } => set_early_unlock(deps, info, early_unlock),
+        ExecuteMsg::EarlyUnlock { amount } => early_unlock(deps, _env, info, amount),
+        ExecuteMsg::SetRewardDenoms { denoms } => set_reward_denoms(deps, info, denoms),
+        ExecuteMsg::DistributeRewards {} => distribute_rewards(deps, _env, info),
+        ExecuteMsg::ClaimRewards { grant_id } => claim_rewards(deps, _env, info, grant_id),
+        ExecuteMsg::ScheduleConfigChange { change, effective_time } =>
+            schedule_config_change(deps, _env, info, change, effective_time),
+        ExecuteMsg::CancelConfigChange { id } => cancel_config_change(deps, info, id),
+        ExecuteMsg::ExecuteConfigChange { id } => execute_config_change(deps, _env, id),
+        ExecuteMsg::SetApprovals { approvals } => set_approvals(deps, info, approvals),
+        ExecuteMsg::Propose { msg } => propose(deps, _env, info, *msg),
+        ExecuteMsg::Approve { id } => approve(deps, _env, info, id),
+        ExecuteMsg::ExecuteProposal { id } => execute_proposal(deps, _env, id),
     }
 }
 
-pub fn change_admin(
+// Admin actions that have to go through a proposal while approvals are enabled. Deposit is
+// left out as it only adds funds, it can't be proposed anyway since proposals carry none
+fn is_privileged(msg: &ExecuteMsg) -> bool {
+    matches!(
+        msg,
+        ExecuteMsg::VestTokens { .. } |
+            ExecuteMsg::ChangeAdmin { .. } |
+            ExecuteMsg::ChangeInstantClaimPercents { .. } |
+            ExecuteMsg::ChangeLockMinutes { .. } |
+            ExecuteMsg::RegisterMerkleRoot { .. } |
+            ExecuteMsg::ChangeVoucherSigner { .. } |
+            ExecuteMsg::SetGrantNft { .. } |
+            ExecuteMsg::SetExtraDenoms { .. } |
+            ExecuteMsg::SetMaxTotalAllocation { .. } |
+            ExecuteMsg::ChangeTreasury { .. } |
+            ExecuteMsg::IncreaseGrant { .. } |
+            ExecuteMsg::DecreaseGrant { .. } |
+            ExecuteMsg::SetEarlyUnlock { .. } |
+            ExecuteMsg::SetRewardDenoms { .. } |
+            ExecuteMsg::ScheduleConfigChange { .. } |
+            ExecuteMsg::CancelConfigChange { .. } |
+            ExecuteMsg::SetApprovals { .. }
+    )
+}
+
+/// Enables the approval workflow, or disables it with None. Once enabled this is privileged
+/// as well, so approvers can only be changed through a proposal
+pub fn set_approvals(
     deps: DepsMut,
     info: MessageInfo,
-    new_admin: String
-) -> Result<Response, ContractError> {
+    approvals: Option<ApprovalsMsg>
+) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
     check_is_admin(deps.as_ref(), info.sender)?;
 
-    ADMIN.save(deps.storage, &deps.api.addr_validate(&new_admin)?)?;
+    let approvals = approvals.map(|approvals| validate_approvals(deps.api, approvals)).transpose()?;
+
+    let mut config = CONFIG.load(deps.storage)?;
+    let old_value = approvals_value(&config.approvals)?;
+
+    config.approvals = approvals;
+
+    CONFIG.save(deps.storage, &config)?;
 
-    Ok(Response::new().add_attribute("method", "change_admin"))
+    Ok(
+        Response::new()
+            .add_event(
+                config_changed_event("approvals", old_value, approvals_value(&config.approvals)?)
+            )
+            .add_attribute("method", "set_approvals")
+    )
 }
 
-pub fn change_lock_minutes(
+/// Opens a proposal to run a privileged `msg` as the admin, the proposer approves it right away
+pub fn propose(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
-    new_lock_minutes: Uint256
-) -> Result<Response, ContractError> {
-    check_is_admin(deps.as_ref(), info.sender)?;
+    msg: ExecuteMsg
+) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
+    let approvals = CONFIG.load(deps.storage)?.approvals.ok_or(
+        ContractError::ApprovalsDisabled {}
+    )?;
 
-    if new_lock_minutes == Uint256::zero() {
-        return Err(ContractError::InvalidLockMinutes {});
+    if !approvals.approvers.contains(&info.sender) {
+        return Err(ContractError::NotApprover {});
     }
 
-    CONFIG.update(
-        deps.storage,
-        |mut config| -> Result<Config, StdError> {
-            config.lock_minutes = new_lock_minutes;
+    if !info.funds.is_empty() {
+        return Err(ContractError::InvalidFunds {});
+    }
 
-            Ok(config)
-        }
+    if !is_privileged(&msg) {
+        return Err(ContractError::NotPrivileged {});
+    }
+
+    let id = PROPOSAL_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    let expires = env.block.time.plus_seconds(approvals.proposal_ttl);
+
+    PROPOSAL_COUNT.save(deps.storage, &id)?;
+    PROPOSALS.save(
+        deps.storage,
+        id,
+        &(Proposal {
+            id,
+            proposer: info.sender.clone(),
+            msg: msg.clone(),
+            approvals: vec![info.sender.clone()],
+            expires,
+            executed: false,
+        })
     )?;
 
-    Ok(Response::new().add_attribute("method", "change_lock_minutes"))
+    Ok(
+        Response::new()
+            .add_event(
+                Event::new("proposal_created")
+                    .add_attribute("id", id.to_string())
+                    .add_attribute("proposer", info.sender)
+                    .add_attribute("msg", to_json_string(&msg)?)
+                    .add_attribute("expires", expires.seconds().to_string())
+            )
+            .add_attribute("method", "propose")
+    )
 }
 
-pub fn change_instant_percents(
+pub fn approve(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
-    new_instant_percents: Uint256
-) -> Result<Response, ContractError> {
-    check_is_admin(deps.as_ref(), info.sender)?;
+    id: u64
+) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
+    let approvals = CONFIG.load(deps.storage)?.approvals.ok_or(
+        ContractError::ApprovalsDisabled {}
+    )?;
 
-    if new_instant_percents > PERCENTS {
-        return Err(ContractError::InvalidPercents {});
+    if !approvals.approvers.contains(&info.sender) {
+        return Err(ContractError::NotApprover {});
     }
 
-    CONFIG.update(
-        deps.storage,
-        |mut config| -> Result<Config, StdError> {
-            config.instant_claim_percents = new_instant_percents;
+    let mut proposal = load_open_proposal(deps.storage, &env, id)?;
 
-            Ok(config)
-        }
-    )?;
+    if proposal.approvals.contains(&info.sender) {
+        return Err(ContractError::AlreadyApproved {});
+    }
 
-    Ok(Response::new().add_attribute("method", "change_instant_percents"))
-}
+    proposal.approvals.push(info.sender.clone());
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::GetInjxToken {} => to_json_binary(&query_injx_token(deps)?),
-        QueryMsg::GetClaimableAmount { addr } =>
-            to_json_binary(&query_claimable_amount(deps, _env, addr)?),
-        QueryMsg::GetInstantClaim { addr } => to_json_binary(&query_instant_amount(deps, addr)?),
-        QueryMsg::GetTotalClaimed {} => to_json_binary(&query_total_claimed(deps)?),
-        QueryMsg::GetTotalVested {} => to_json_binary(&query_total_vested(deps)?),
-        QueryMsg::GetVestedAmount { addr } =>
-            to_json_binary(&query_user_vesting_info(deps, _env, addr)?),
-        QueryMsg::GetConfig {} => to_json_binary(&query_config(deps)?),
-    }
+    PROPOSALS.save(deps.storage, id, &proposal)?;
+
+    Ok(
+        Response::new()
+            .add_event(
+                Event::new("proposal_approved")
+                    .add_attribute("id", id.to_string())
+                    .add_attribute("approver", info.sender)
+                    .add_attribute(
+                        "approvals",
+                        approval_count(&approvals, &proposal).to_string()
+                    )
+                    .add_attribute("threshold", approvals.threshold.to_string())
+            )
+            .add_attribute("method", "approve")
+    )
 }
 
-pub fn vest_tokens(
-    users: Vec<UserVesting>,
+/// Anyone can execute a proposal that reached the threshold before it expired. Only approvals
+/// of current approvers count, in case the set changed since they were given
+pub fn execute_proposal(
     deps: DepsMut,
     env: Env,
-    info: MessageInfo
-) -> Result<Response, ContractError> {
-    check_is_admin(deps.as_ref(), info.sender)?;
-
-    let mut new_total_amount = Uint256::zero();
-
-    if info.funds.len() != 1 {
-        return Err(ContractError::InvalidFunds {});
-    }
-
-    if users.len() == 0 {
-        return Err(ContractError::NoUsers {});
-    }
-
-    let config = CONFIG.load(deps.storage).unwrap();
+    id: u64
+) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
+    let approvals = CONFIG.load(deps.storage)?.approvals.ok_or(
+        ContractError::ApprovalsDisabled {}
+    )?;
 
-    let coin = &info.funds[0];
+    let mut proposal = load_open_proposal(deps.storage, &env, id)?;
 
-    if coin.denom != config.injex_token {
-        return Err(ContractError::InvalidCoin {});
+    if approval_count(&approvals, &proposal) < approvals.threshold {
+        return Err(ContractError::ProposalNotApproved {});
     }
 
-    for user in users {
-        if user.amount == Uint256::zero() {
-            return Err(ContractError::InvalidInjxAmount {});
-        }
+    proposal.executed = true;
 
-        let instant_claim_amount = (user.amount * config.instant_claim_percents) / PERCENTS;
+    PROPOSALS.save(deps.storage, id, &proposal)?;
 
-        INSTANT_CLAIMABLE_AMOUNT.save(
-            deps.storage,
-            Addr::unchecked(user.user.clone()),
-            &instant_claim_amount
-        )?;
+    let info = MessageInfo {
+        sender: ADMIN.load(deps.storage)?,
+        funds: vec![],
+    };
 
-        let remaining_amount = user.amount - instant_claim_amount;
+    let response = dispatch(deps, env, info, proposal.msg)?;
 
-        let current_time = env.block.time;
+    Ok(
+        response
+            .add_event(Event::new("proposal_executed").add_attribute("id", id.to_string()))
+            .add_attribute("proposal_id", id.to_string())
+    )
+}
 
-        CLAIMABLE_AMOUNT.save(
-            deps.storage,
-            Addr::unchecked(user.user.clone()),
-            &(UserClaims {
-                amount: remaining_amount / config.lock_periods,
-                last_claimed: current_time,
-                amount_claimed: Uint256::zero(),
-                init_vesting: current_time,
-            })
-        )?;
+fn load_open_proposal(
+    storage: &dyn Storage,
+    env: &Env,
+    id: u64
+) -> Result<Proposal, ContractError> {
+    let proposal = PROPOSALS.may_load(storage, id)?.ok_or(ContractError::ProposalNotFound {})?;
 
-        new_total_amount += user.amount;
+    if proposal.executed {
+        return Err(ContractError::ProposalAlreadyExecuted {});
     }
 
-    if new_total_amount != Uint256::from_uint128(info.funds[0].amount) {
-        return Err(ContractError::InvalidFunds {});
+    if env.block.time >= proposal.expires {
+        return Err(ContractError::ProposalExpired {});
     }
 
-    STATE.update(
-        deps.storage,
-        |mut state| -> Result<State, StdError> {
-            state.total_vested += new_total_amount;
-
-            Ok(state)
-        }
-    ).unwrap();
-
-    Ok(Response::new().add_attribute("method", "vest"))
+    Ok(proposal)
 }
 
-pub fn claim(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
-    let sender = info.sender;
-    let config = CONFIG.load(deps.storage).unwrap();
+fn approval_count(approvals: &ApprovalConfig, proposal: &Proposal) -> u32 {
+    proposal.approvals
+        .iter()
+        .filter(|approver| approvals.approvers.contains(approver))
+        .count() as u32
+}
 
-    let instant_claim = INSTANT_CLAIMABLE_AMOUNT.load(deps.storage, sender.clone()).unwrap_or(
-        Uint256::zero()
-    );
+pub fn change_admin(
+    deps: DepsMut,
+    info: MessageInfo,
+    new_admin: String
+) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
+    check_is_admin(deps.as_ref(), info.sender)?;
 
-    let claim = CLAIMABLE_AMOUNT.load(deps.storage, sender.clone()).unwrap_or(UserClaims {
-        amount: Uint256::zero(),
-        last_claimed: Timestamp::from_seconds(0),
-        amount_claimed: Uint256::zero(),
-        init_vesting: Timestamp::from_seconds(0),
-    });
+    let old_admin = ADMIN.load(deps.storage)?;
+    let new_admin = deps.api.addr_validate(&new_admin)?;
 
-    let curr_time = env.block.time;
+    ADMIN.save(deps.storage, &new_admin)?;
 
-    if claim.amount_claimed == claim.amount * config.lock_periods {
-        return Err(ContractError::CannotClaim {});
-    }
+    Ok(
+        Response::new()
+            .add_event(
+                Event::new("admin_changed")
+                    .add_attribute("old_admin", old_admin)
+                    .add_attribute("new_admin", new_admin)
+            )
+            .add_attribute("method", "change_admin")
+    )
+}
 
-    let periods = u64::from_str(&(claim.amount_claimed / claim.amount).to_string()).unwrap();
+/// Lock minutes and instant percents are timelocked, they only change through
+/// ScheduleConfigChange. The direct messages are kept to point callers there
+pub fn change_timelocked(
+    deps: Deps,
+    info: MessageInfo
+) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
+    check_is_admin(deps, info.sender)?;
 
-    let reward = calculate_reward_amount(
-        claim.amount,
-        curr_time.seconds(),
-        claim.init_vesting.seconds(),
-        periods,
-        u64::from_str(&config.lock_minutes.to_string()).unwrap(),
-        claim.amount * config.lock_periods - claim.amount_claimed
-    ).unwrap();
+    Err(ContractError::TimelockRequired {})
+}
 
-    if reward == Uint256::zero() && instant_claim == Uint256::zero() {
-        return Err(ContractError::CannotClaim {});
-    }
+/// Queues `change` to be applied at `effective_time`, at least min_delay from now
+pub fn schedule_config_change(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    change: ConfigChange,
+    effective_time: Timestamp
+) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
+    check_is_admin(deps.as_ref(), info.sender)?;
 
-    let balance_res = deps.querier.query_balance(
-        env.clone().contract.address.to_string(),
-        config.injex_token.clone()
-    )?;
+    validate_config_change(&change)?;
 
-    let balance = balance_res.amount;
+    let config = CONFIG.load(deps.storage)?;
 
-    if Uint256::from_uint128(balance) < reward + instant_claim {
-        return Err(ContractError::InsufficientContractBalance {});
+    if effective_time < env.block.time.plus_seconds(config.min_delay) {
+        return Err(ContractError::EffectiveTimeTooEarly {});
     }
 
-    let send_msg = BankMsg::Send {
-        to_address: sender.clone().to_string(),
-        amount: vec![Coin {
-            amount: Uint128::from_str(&(reward + instant_claim).to_string())?,
-            denom: config.injex_token.to_string(),
-        }],
-    };
-
-    if instant_claim != Uint256::zero() {
-        INSTANT_CLAIMABLE_AMOUNT.update(
-            deps.storage,
-            sender.clone(),
-            |_| -> Result<Uint256, StdError> { Ok(Uint256::zero()) }
-        )?;
-    }
+    let id = CONFIG_CHANGE_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
 
-    CLAIMABLE_AMOUNT.update(
+    CONFIG_CHANGE_COUNT.save(deps.storage, &id)?;
+    PENDING_CONFIG_CHANGES.save(
         deps.storage,
-        sender.clone(),
-        |claim| -> Result<UserClaims, StdError> {
-            let mut claim = claim.unwrap();
-            claim.last_claimed = curr_time;
-            claim.amount_claimed += reward;
-
-            Ok(claim)
-        }
+        id,
+        &(PendingConfigChange {
+            id,
+            change: change.clone(),
+            effective_time,
+        })
     )?;
 
-    STATE.update(
-        deps.storage,
-        |mut state| -> Result<State, StdError> {
-            state.total_claimed += reward + instant_claim;
-
-            Ok(state)
-        }
-    ).unwrap();
-
     Ok(
         Response::new()
-            .add_message(CosmosMsg::Bank(send_msg))
-            .add_attribute("user", sender.clone())
-            .add_attribute("amount_claimed", reward + instant_claim)
-            .add_attribute("method", "execute_claim")
+            .add_event(
+                Event::new("config_change_scheduled")
+                    .add_attribute("id", id.to_string())
+                    .add_attribute("change", to_json_string(&change)?)
+                    .add_attribute("effective_time", effective_time.seconds().to_string())
+            )
+            .add_attribute("method", "schedule_config_change")
     )
 }
 
-pub fn query_claimable_amount(deps: Deps, env: Env, addr: String) -> StdResult<Uint256> {
-    let user = Addr::unchecked(addr);
-    let config = CONFIG.load(deps.storage).unwrap();
+pub fn cancel_config_change(
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64
+) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
+    check_is_admin(deps.as_ref(), info.sender)?;
 
-    let instant_claim = INSTANT_CLAIMABLE_AMOUNT.load(deps.storage, user.clone()).unwrap_or(
-        Uint256::zero()
-    );
+    if !PENDING_CONFIG_CHANGES.has(deps.storage, id) {
+        return Err(ContractError::ConfigChangeNotFound {});
+    }
 
-    let claim = CLAIMABLE_AMOUNT.load(deps.storage, user.clone()).unwrap_or(UserClaims {
-        amount: Uint256::zero(),
-        last_claimed: Timestamp::from_seconds(0),
-        amount_claimed: Uint256::zero(),
-        init_vesting: Timestamp::from_seconds(0),
-    });
+    PENDING_CONFIG_CHANGES.remove(deps.storage, id);
 
-    let curr_time = env.block.time;
+    Ok(
+        Response::new()
+            .add_event(Event::new("config_change_cancelled").add_attribute("id", id.to_string()))
+            .add_attribute("method", "cancel_config_change")
+    )
+}
+
+/// Anyone can apply a scheduled change once its effective time has come
+pub fn execute_config_change(
+    deps: DepsMut,
+    env: Env,
+    id: u64
+) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
+    let pending = PENDING_CONFIG_CHANGES.may_load(deps.storage, id)?.ok_or(
+        ContractError::ConfigChangeNotFound {}
+    )?;
 
-    if claim.amount_claimed == claim.amount * config.lock_periods {
-        return Ok(Uint256::zero());
+    if env.block.time < pending.effective_time {
+        return Err(ContractError::ConfigChangeNotReady {});
     }
 
-    let periods = u64::from_str(&(claim.amount_claimed / claim.amount).to_string()).unwrap();
+    PENDING_CONFIG_CHANGES.remove(deps.storage, id);
 
-    let reward = calculate_reward_amount(
-        claim.amount,
-        curr_time.seconds(),
-        claim.init_vesting.seconds(),
-        periods,
-        u64::from_str(&config.lock_minutes.to_string()).unwrap(),
-        claim.amount * config.lock_periods - claim.amount_claimed
-    ).unwrap();
+    let event = apply_config_change(deps.storage, &pending.change, env.block.time)?;
 
-    Ok(reward + instant_claim)
+    Ok(
+        Response::new()
+            .add_event(event)
+            .add_event(Event::new("config_change_executed").add_attribute("id", id.to_string()))
+            .add_attribute("method", "execute_config_change")
+    )
 }
 
-pub fn query_total_claimed(deps: Deps) -> StdResult<Addr> {
-    let state = STATE.load(deps.storage).unwrap();
+pub fn change_voucher_signer(
+    deps: DepsMut,
+    info: MessageInfo,
+    new_signer: Option<VoucherSigner>
+) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
+    check_is_admin(deps.as_ref(), info.sender)?;
 
-    Ok(Addr::unchecked(state.total_claimed))
-}
+    if let Some(signer) = &new_signer {
+        validate_voucher_signer(signer)?;
+    }
 
-pub fn query_instant_amount(deps: Deps, addr: String) -> StdResult<Uint256> {
-    let amount = INSTANT_CLAIMABLE_AMOUNT.load(deps.storage, Addr::unchecked(addr)).unwrap();
+    let mut config = CONFIG.load(deps.storage)?;
+    let old_signer = voucher_signer_value(&config.voucher_signer)?;
 
-    Ok(amount)
-}
+    config.voucher_signer = new_signer;
 
-pub fn query_config(deps: Deps) -> StdResult<Config> {
-    let config = CONFIG.load(deps.storage).unwrap();
+    CONFIG.save(deps.storage, &config)?;
 
-    Ok(config)
+    Ok(
+        Response::new()
+            .add_event(
+                config_changed_event(
+                    "voucher_signer",
+                    old_signer,
+                    voucher_signer_value(&config.voucher_signer)?
+                )
+            )
+            .add_attribute("method", "change_voucher_signer")
+    )
 }
 
-pub fn query_user_vesting_info(deps: Deps, env: Env, addr: String) -> StdResult<UserVestingInfo> {
-    let user = Addr::unchecked(addr);
-    let config = CONFIG.load(deps.storage).unwrap();
-    let vesting = CLAIMABLE_AMOUNT.load(deps.storage, user.clone()).unwrap();
-    let instant = INSTANT_CLAIMABLE_AMOUNT.load(deps.storage, user.clone()).unwrap();
+pub fn set_grant_nft(
+    deps: DepsMut,
+    info: MessageInfo,
+    nft_contract: String
+) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
+    check_is_admin(deps.as_ref(), info.sender)?;
 
-    let lock_minutes = u64::from_str(&config.lock_minutes.to_string()).unwrap();
-    let remaining_reward = vesting.amount * config.lock_periods - vesting.amount_claimed;
+    // Switching modes after grants were created would leave them without tokens
+    if GRANT_COUNT.may_load(deps.storage)?.unwrap_or_default() != 0 {
+        return Err(ContractError::GrantsAlreadyCreated {});
+    }
 
-    let curr_time = env.block.time.seconds();
-    let time_passed = (curr_time - vesting.init_vesting.seconds()) / 60;
-    let periods: u64 = u64
-        ::from_str(&(vesting.amount_claimed / vesting.amount).to_string())
-        .unwrap();
+    let nft_contract = deps.api.addr_validate(&nft_contract)?;
 
-    let next_claim = if time_passed - periods * lock_minutes >= lock_minutes {
-        Uint256::zero()
-    } else {
-        Uint256::from_u128(
-            (vesting.init_vesting.seconds() + lock_minutes * (periods + 1) * 60).into()
-        )
-    };
+    let mut config = CONFIG.load(deps.storage)?;
+    let old_nft_contract = config.grant_nft.map(String::from).unwrap_or_default();
 
-    Ok(UserVestingInfo {
-        full_amount: remaining_reward + instant,
-        next_claim,
-        claimed: vesting.amount_claimed,
-    })
-}
+    config.grant_nft = Some(nft_contract.clone());
 
-pub fn query_total_vested(deps: Deps) -> StdResult<Addr> {
-    let state = STATE.load(deps.storage).unwrap();
+    CONFIG.save(deps.storage, &config)?;
 
-    Ok(Addr::unchecked(state.total_vested))
+    Ok(
+        Response::new()
+            .add_event(config_changed_event("grant_nft", old_nft_contract, nft_contract.clone()))
+            .add_attribute("method", "set_grant_nft")
+            .add_attribute("nft_contract", nft_contract)
+    )
 }
 
-pub fn query_injx_token(deps: Deps) -> StdResult<Addr> {
-    let config: Config = CONFIG.load(deps.storage).unwrap();
+pub fn set_extra_denoms(
+    deps: DepsMut,
+    info: MessageInfo,
+    denoms: Vec<String>
+) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
+    check_is_admin(deps.as_ref(), info.sender)?;
 
-    Ok(Addr::unchecked(config.injex_token))
-}
+    let mut config = CONFIG.load(deps.storage)?;
 
-fn calculate_reward_amount(
-    reward: Uint256,
-    curr_time: u64,
-    instant_time: u64,
-    periods_claimed: u64,
-    period_time: u64,
-    max_reward: Uint256
-) -> StdResult<Uint256> {
-    let minutes_passed = (curr_time - instant_time) / 60;
+    validate_extra_denoms(&config.injex_token, &denoms)?;
 
-    let periods_passed = minutes_passed / period_time - periods_claimed;
+    // Positions in denoms removed from the whitelist stay claimable
+    let old_denoms = config.extra_denoms.join(",");
 
-    let mut full_reward = reward * Uint256::from_u128(periods_passed.into());
+    config.extra_denoms = denoms;
+
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(
+        Response::new()
+            .add_event(
+                config_changed_event("extra_denoms", old_denoms, config.extra_denoms.join(","))
+            )
+            .add_attribute("method", "set_extra_denoms")
+    )
+}
+
+#[cfg_attr(not(any(feature = "library", feature = "factory")), entry_point)]
+pub fn sudo(
+    deps: DepsMut,
+    env: Env,
+    msg: SudoMsg
+) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
+    match msg {
+        SudoMsg::ReplaceAdmin { new_admin } => sudo_replace_admin(deps, new_admin),
+        SudoMsg::SetPaused { paused } => sudo_set_paused(deps, paused),
+        SudoMsg::RevokeGrant { user, redistribute } =>
+            sudo_revoke_grant(deps, env, user, redistribute),
+    }
+}
+
+pub fn sudo_replace_admin(
+    deps: DepsMut,
+    new_admin: String
+) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
+    let new_admin = deps.api.addr_validate(&new_admin)?;
+    let old_admin = ADMIN.load(deps.storage)?;
+
+    ADMIN.save(deps.storage, &new_admin)?;
+
+    Ok(
+        Response::new()
+            .add_event(
+                Event::new("admin_changed")
+                    .add_attribute("old_admin", old_admin)
+                    .add_attribute("new_admin", new_admin.clone())
+                    .add_attribute("source", "sudo")
+            )
+            .add_attribute("method", "sudo_replace_admin")
+            .add_attribute("new_admin", new_admin)
+    )
+}
+
+pub fn sudo_set_paused(
+    deps: DepsMut,
+    paused: bool
+) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
+    let old_paused = PAUSED.may_load(deps.storage)?.unwrap_or_default();
+
+    PAUSED.save(deps.storage, &paused)?;
+
+    Ok(
+        Response::new()
+            .add_event(config_changed_event("paused", old_paused, paused))
+            .add_attribute("method", "sudo_set_paused")
+    )
+}
+
+/// Stops all future unlocks of the grant, extra denoms included. What already unlocked,
+/// including the instant part, stays claimable and the locked rest goes the same way as a
+/// DecreaseGrant refund.
+pub fn sudo_revoke_grant(
+    mut deps: DepsMut,
+    env: Env,
+    user: String,
+    redistribute: bool
+) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
+    let user = deps.api.addr_validate(&user)?;
+
+    let grant_id = GRANT_IDS.may_load(deps.storage, user.clone())?.ok_or(
+        ContractError::GrantNotFound {}
+    )?;
+
+    let mut response = rebase_grant(
+        deps.branch(),
+        env.clone(),
+        user.clone(),
+        grant_id,
+        None,
+        false,
+        redistribute
+    )?;
+
+    let config = CONFIG.load(deps.storage)?;
+    let revoked = revoke_extra_grants(deps.storage, &config, &user, env.block.time)?;
+
+    let mut refund: Vec<Coin> = vec![];
+
+    for coin in &revoked {
+        if !(redistribute && distribute_extra(deps.storage, &user, coin, env.block.time)?) {
+            refund.push(coin.clone());
+        }
+    }
+
+    // without a treasury the refund stays in the pool of its denom
+    if let Some(treasury) = &config.treasury {
+        if !refund.is_empty() {
+            response = response.add_message(BankMsg::Send {
+                to_address: treasury.to_string(),
+                amount: refund,
+            });
+        }
+    }
+
+    Ok(
+        response
+            .add_event(
+                Event::new("grant_revoked")
+                    .add_attribute("beneficiary", user)
+                    .add_attribute("grant_id", grant_id.to_string())
+                    .add_attribute("redistributed", redistribute.to_string())
+                    .add_attribute("extra_amounts", coins_value(&revoked))
+            )
+            .add_attribute("method", "sudo_revoke_grant")
+    )
+}
+
+// Takes the locked part off every extra denom position of `user` and returns it. What has
+// unlocked is moved to the instant part, which leaves nothing to vest
+fn revoke_extra_grants(
+    storage: &mut dyn Storage,
+    config: &Config,
+    user: &Addr,
+    time: Timestamp
+) -> StdResult<Vec<Coin>> {
+    let claim = CLAIMABLE_AMOUNT.load(storage, user.clone())?;
+    let mut state = STATE.load(storage)?;
+
+    let denom_claims = EXTRA_CLAIMS.prefix(user.clone())
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(String, DenomClaims)>>>()?;
+
+    let mut revoked: Vec<Coin> = vec![];
+
+    for (denom, denom_claim) in denom_claims {
+        let position = position_at(
+            config,
+            denom_claim.instant_claimable,
+            &denom_schedule(&claim, &denom_claim),
+            time
+        )?;
+
+        if position.locked == Uint256::zero() {
+            continue;
+        }
+
+        EXTRA_CLAIMS.save(
+            storage,
+            (user.clone(), denom.clone()),
+            &(DenomClaims {
+                amount: Uint256::zero(),
+                amount_claimed: Uint256::zero(),
+                instant_claimable: denom_claim.instant_claimable + position.periodic_claimable,
+                ..denom_claim
+            })
+        )?;
+
+        state.denom_totals(&denom).total_vested -= position.locked;
+
+        revoked.push(Coin {
+            amount: Uint128::try_from(position.locked)?,
+            denom,
+        });
+    }
+
+    STATE.save(storage, &state)?;
+
+    Ok(revoked)
+}
+
+#[cfg_attr(not(any(feature = "library", feature = "factory")), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::GetInjxToken {} => to_json_binary(&query_injx_token(deps)?),
+        QueryMsg::GetClaimableAmount { addr } =>
+            to_json_binary(&query_claimable_amount(deps, _env, addr)?),
+        QueryMsg::GetInstantClaim { addr } => to_json_binary(&query_instant_amount(deps, addr)?),
+        QueryMsg::GetExtraClaimable { addr } =>
+            to_json_binary(&query_extra_claimable(deps, _env, addr)?),
+        QueryMsg::GetTotalClaimed {} => to_json_binary(&query_total_claimed(deps)?),
+        QueryMsg::GetTotalVested {} => to_json_binary(&query_total_vested(deps)?),
+        QueryMsg::GetTotals {} => to_json_binary(&query_totals(deps)?),
+        QueryMsg::IsPaused {} => to_json_binary(&query_is_paused(deps)?),
+        QueryMsg::GetUnallocated {} => to_json_binary(&query_unallocated(deps, _env)?),
+        QueryMsg::Stats {} => to_json_binary(&query_stats(deps, _env)?),
+        QueryMsg::CheckSolvency {} => to_json_binary(&query_check_solvency(deps, _env)?),
+        QueryMsg::AuditPositions { start_after, limit } =>
+            to_json_binary(&query_audit_positions(deps, start_after, limit)?),
+        QueryMsg::GetVestedAmount { addr } =>
+            to_json_binary(&query_user_vesting_info(deps, _env, addr)?),
+        QueryMsg::GetConfig {} => to_json_binary(&query_config(deps)?),
+        QueryMsg::VotingPowerAtHeight { address, height } =>
+            to_json_binary(&query_voting_power_at_height(deps, _env, address, height)?),
+        QueryMsg::TotalPowerAtHeight { height } =>
+            to_json_binary(&query_total_power_at_height(deps, _env, height)?),
+        QueryMsg::Info {} => to_json_binary(&query_info(deps)?),
+        QueryMsg::Dao {} => to_json_binary(&DAO.load(deps.storage)?),
+        QueryMsg::GetMerkleAllocation {} => to_json_binary(&query_merkle_allocation(deps)?),
+        QueryMsg::IsRegistered { addr } => to_json_binary(&query_is_registered(deps, addr)?),
+        QueryMsg::IsVoucherNonceUsed { nonce } =>
+            to_json_binary(&query_is_voucher_nonce_used(deps, nonce)?),
+        QueryMsg::GetGrant { grant_id } => to_json_binary(&query_grant(deps, grant_id)?),
+        QueryMsg::SimulateAt { addr, timestamp } =>
+            to_json_binary(&query_simulate_at(deps, addr, timestamp)?),
+        QueryMsg::UnlockSchedule { addr } => to_json_binary(&query_unlock_schedule(deps, addr)?),
+        QueryMsg::ClaimHistory { addr, start_after, limit } =>
+            to_json_binary(&query_claim_history(deps, addr, start_after, limit)?),
+        QueryMsg::QuoteEarlyUnlock { addr, amount } =>
+            to_json_binary(&query_quote_early_unlock(deps, _env, addr, amount)?),
+        QueryMsg::PendingRewards { addr } =>
+            to_json_binary(&query_pending_rewards(deps, _env, addr)?),
+        QueryMsg::Rewards {} => to_json_binary(&query_rewards(deps, _env)?),
+        QueryMsg::PendingConfigChanges { start_after, limit } =>
+            to_json_binary(&query_pending_config_changes(deps, start_after, limit)?),
+        QueryMsg::GetProposal { id } => to_json_binary(&query_proposal(deps, id)?),
+        QueryMsg::OpenProposals { start_after, limit } =>
+            to_json_binary(&query_open_proposals(deps, _env, start_after, limit)?),
+    }
+}
+
+pub fn vest_tokens(
+    users: Vec<UserVesting>,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo
+) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
+    check_is_admin(deps.as_ref(), info.sender)?;
+
+    let mut new_total_amount = Uint256::zero();
+    let mut extra_amounts: BTreeMap<String, Uint256> = BTreeMap::new();
+
+    if users.is_empty() {
+        return Err(ContractError::NoUsers {});
+    }
+
+    let config = CONFIG.load(deps.storage).unwrap();
+
+    // Attached funds are deposited into the pool before the batch is allocated from it
+    check_deposit_funds(&config, &info.funds)?;
+
+    // Minted grants are only obligations, there is nothing to fund or to pay extras from
+    if config.mint_cap.is_some() {
+        if !info.funds.is_empty() {
+            return Err(ContractError::InvalidFunds {});
+        }
+
+        if users.iter().any(|user| !user.extra.is_empty()) {
+            return Err(ContractError::InvalidCoin {});
+        }
+    }
+
+    // The whole batch is validated before anything is written
+    for user in &users {
+        if user.amount == Uint256::zero() {
+            return Err(ContractError::InvalidInjxAmount {});
+        }
+
+        for (index, coin) in user.extra.iter().enumerate() {
+            if !config.extra_denoms.contains(&coin.denom) {
+                return Err(ContractError::InvalidCoin {});
+            }
+
+            if
+                coin.amount.is_zero() ||
+                user.extra[..index].iter().any(|other| other.denom == coin.denom)
+            {
+                return Err(ContractError::InvalidFunds {});
+            }
+
+            *extra_amounts.entry(coin.denom.clone()).or_default() += Uint256::from_uint128(
+                coin.amount
+            );
+        }
+
+        new_total_amount += user.amount;
+    }
+
+    check_allocation_cap(deps.as_ref(), &config, new_total_amount)?;
+    check_unallocated(deps.as_ref(), &env, &config, new_total_amount)?;
+
+    for (denom, amount) in &extra_amounts {
+        if unallocated_amount(deps.as_ref(), &env, denom)? < *amount {
+            return Err(ContractError::InsufficientUnallocated {});
+        }
+    }
+
+    let mut mint_msgs: Vec<CosmosMsg<InjectiveMsgWrapper>> = vec![];
+    let mut events: Vec<Event> = vec![];
+
+    for user in users {
+        let beneficiary = Addr::unchecked(user.user.clone());
+
+        let (mut event, mint_msg) = create_vesting(
+            deps.storage,
+            &config,
+            &beneficiary,
+            user.amount,
+            env.block.time,
+            &env.block,
+            "vest_tokens"
+        )?;
+
+        if !user.extra.is_empty() {
+            create_extra_vesting(deps.storage, &config, &beneficiary, &user.extra)?;
+
+            event = event.add_attribute("extra_amounts", coins_value(&user.extra));
+        }
+
+        events.push(event);
+        mint_msgs.extend(mint_msg);
+    }
+
+    STATE.update(
+        deps.storage,
+        |mut state| -> Result<State, StdError> {
+            state.total_vested += new_total_amount;
+
+            Ok(state)
+        }
+    ).unwrap();
+
+    Ok(
+        Response::new()
+            .add_messages(mint_msgs)
+            .add_events(events)
+            .add_attribute("method", "vest")
+    )
+}
+
+pub fn deposit(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo
+) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
+    check_is_admin(deps.as_ref(), info.sender.clone())?;
+
+    let config = CONFIG.load(deps.storage)?;
+
+    if config.mint_cap.is_some() {
+        return Err(ContractError::NotSupportedInMintMode {});
+    }
+
+    if info.funds.is_empty() {
+        return Err(ContractError::InvalidFunds {});
+    }
+
+    check_deposit_funds(&config, &info.funds)?;
+
+    let mut event = Event::new("deposit")
+        .add_attribute("sender", info.sender)
+        .add_attribute("amount", coins_value(&info.funds));
+
+    for coin in &info.funds {
+        event = event.add_attribute(
+            format!("unallocated_{}", coin.denom),
+            unallocated_amount(deps.as_ref(), &env, &coin.denom)?
+        );
+    }
+
+    Ok(Response::new().add_event(event).add_attribute("method", "deposit"))
+}
+
+pub fn set_max_total_allocation(
+    deps: DepsMut,
+    info: MessageInfo,
+    max_total_allocation: Option<Uint256>
+) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
+    check_is_admin(deps.as_ref(), info.sender)?;
+
+    if let Some(cap) = max_total_allocation {
+        if cap < total_allocated(deps.storage)? {
+            return Err(ContractError::AllocationCapExceeded {});
+        }
+    }
+
+    let mut config = CONFIG.load(deps.storage)?;
+    let old_cap = config.max_total_allocation.map(|cap| cap.to_string()).unwrap_or_default();
+
+    config.max_total_allocation = max_total_allocation;
+
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(
+        Response::new()
+            .add_event(
+                config_changed_event(
+                    "max_total_allocation",
+                    old_cap,
+                    max_total_allocation.map(|cap| cap.to_string()).unwrap_or_default()
+                )
+            )
+            .add_attribute("method", "set_max_total_allocation")
+    )
+}
+
+pub fn change_treasury(
+    deps: DepsMut,
+    info: MessageInfo,
+    new_treasury: Option<String>
+) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
+    check_is_admin(deps.as_ref(), info.sender)?;
+
+    let new_treasury = new_treasury.map(|treasury| deps.api.addr_validate(&treasury)).transpose()?;
+
+    let mut config = CONFIG.load(deps.storage)?;
+    let old_treasury = config.treasury.map(String::from).unwrap_or_default();
+
+    config.treasury = new_treasury;
+
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(
+        Response::new()
+            .add_event(
+                config_changed_event(
+                    "treasury",
+                    old_treasury,
+                    config.treasury.map(String::from).unwrap_or_default()
+                )
+            )
+            .add_attribute("method", "change_treasury")
+    )
+}
+
+/// Changes what the periods that have not unlocked yet release, without touching what was
+/// unlocked or claimed so far. The position is rebased on the current period: everything
+/// unlocked up to now becomes `base_vested` and the new locked amount is split evenly over the
+/// remaining periods. Division remainders are refunded on decrease and not allocated on
+/// increase, so the schedule never owes more than it can pay out.
+pub fn amend_grant(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    user: String,
+    grant_id: u64,
+    amount: Uint256,
+    increase: bool
+) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
+    check_is_admin(deps.as_ref(), info.sender)?;
+
+    let user = deps.api.addr_validate(&user)?;
+
+    if GRANTS.may_load(deps.storage, grant_id)? != Some(user.clone()) {
+        return Err(ContractError::GrantNotFound {});
+    }
+
+    if amount == Uint256::zero() {
+        return Err(ContractError::InvalidInjxAmount {});
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+
+    if increase {
+        check_deposit_funds(&config, &info.funds)?;
+    } else if !info.funds.is_empty() {
+        return Err(ContractError::InvalidFunds {});
+    }
+
+    rebase_grant(deps, env, user, grant_id, Some(amount), increase, false)
+}
+
+// Core of grant amendments, `amount` of None takes off everything that is still locked.
+// With `redistribute` what is taken off is shared among the other positions instead of refunded
+fn rebase_grant(
+    deps: DepsMut,
+    env: Env,
+    user: Addr,
+    grant_id: u64,
+    amount: Option<Uint256>,
+    increase: bool,
+    redistribute: bool
+) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let claim = CLAIMABLE_AMOUNT.load(deps.storage, user.clone())?;
+    let instant = INSTANT_CLAIMABLE_AMOUNT.may_load(
+        deps.storage,
+        user.clone()
+    )?.unwrap_or_default();
+
+    let plan = plan_rebase(&config, instant, &claim, env.block.time, amount, increase)?;
+
+    if increase {
+        check_allocation_cap(deps.as_ref(), &config, plan.applied)?;
+        check_unallocated(deps.as_ref(), &env, &config, plan.applied)?;
+    }
+
+    apply_rebase(deps.storage, &env, &user, instant, &claim, &plan, increase)?;
+
+    let redistributed =
+        !increase &&
+        redistribute &&
+        distribute_bonus(deps.storage, &user, plan.applied, env.block.time)?;
+
+    let mut response = Response::new();
+
+    // nothing is held for minted grants, so there is nothing to refund either
+    if
+        !increase &&
+        !redistributed &&
+        plan.applied != Uint256::zero() &&
+        config.mint_cap.is_none()
+    {
+        if let Some(treasury) = &config.treasury {
+            response = response.add_message(BankMsg::Send {
+                to_address: treasury.to_string(),
+                amount: vec![Coin {
+                    amount: Uint128::from_str(&plan.applied.to_string())?,
+                    denom: config.injex_token.clone(),
+                }],
+            });
+        }
+    }
+
+    Ok(
+        response
+            .add_event(
+                Event::new("grant_amended")
+                    .add_attribute("beneficiary", user)
+                    .add_attribute("grant_id", grant_id.to_string())
+                    .add_attribute("kind", if increase { "increase" } else { "decrease" })
+                    .add_attribute("requested_amount", plan.requested)
+                    .add_attribute("applied_amount", plan.applied)
+                    .add_attribute("amount_per_period", plan.new_claim.amount)
+                    .add_attribute("base_period", plan.periods_passed.to_string())
+                    .add_attribute("base_vested", plan.new_claim.base_vested)
+                    .add_attribute("remaining_periods", plan.remaining_periods)
+            )
+            .add_attribute("method", if increase { "increase_grant" } else { "decrease_grant" })
+    )
+}
+
+// Position a rebase at a given time results in, see amend_grant
+struct RebasePlan {
+    new_claim: UserClaims,
+    requested: Uint256,
+    // what the schedule actually gains or loses, remainders included
+    applied: Uint256,
+    periods_passed: u64,
+    remaining_periods: Uint256,
+}
+
+fn plan_rebase(
+    config: &Config,
+    instant: Uint256,
+    claim: &UserClaims,
+    time: Timestamp,
+    amount: Option<Uint256>,
+    increase: bool
+) -> Result<RebasePlan, ContractError> {
+    let lock_minutes = Uint64::try_from(config.lock_minutes).map_err(StdError::from)?.u64();
+    let lock_periods = Uint64::try_from(config.lock_periods).map_err(StdError::from)?.u64();
+
+    let periods_passed =
+        time.seconds().saturating_sub(claim.init_vesting.seconds()) / 60 / lock_minutes;
+
+    if periods_passed >= lock_periods {
+        return Err(ContractError::GrantFullyVested {});
+    }
+
+    let remaining_periods = Uint256::from(lock_periods - periods_passed);
+
+    let position = position_at(config, instant, claim, time)?;
+    let unlocked = claim.amount_claimed + position.periodic_claimable;
+    let requested = amount.unwrap_or(position.locked);
+
+    let new_locked = if increase {
+        position.locked + requested
+    } else {
+        if requested > position.locked {
+            return Err(ContractError::DecreaseExceedsLocked {});
+        }
+
+        position.locked - requested
+    };
+
+    let new_claim = UserClaims {
+        amount: new_locked / remaining_periods,
+        base_period: periods_passed,
+        base_vested: unlocked,
+        ..claim.clone()
+    };
+
+    let new_total = total_periodic(config, &new_claim);
+    let old_total = total_periodic(config, claim);
+
+    Ok(RebasePlan {
+        applied: if increase { new_total - old_total } else { old_total - new_total },
+        new_claim,
+        requested,
+        periods_passed,
+        remaining_periods,
+    })
+}
+
+fn apply_rebase(
+    storage: &mut dyn Storage,
+    env: &Env,
+    user: &Addr,
+    instant: Uint256,
+    claim: &UserClaims,
+    plan: &RebasePlan,
+    increase: bool
+) -> StdResult<()> {
+    let config = CONFIG.load(storage)?;
+    let mut state = STATE.load(storage)?;
+
+    let new_claim = plan.new_claim.clone();
+    checkpoint_rewards(storage, &state, &config, user, claim, env.block.time)?;
+
+    let old_total = total_periodic(&config, claim);
+    let new_total = total_periodic(&config, &new_claim);
+
+    let was_fully_claimed = instant == Uint256::zero() && claim.amount_claimed == old_total;
+    let is_fully_claimed = instant == Uint256::zero() && claim.amount_claimed == new_total;
+
+    CLAIMABLE_AMOUNT.save(storage, user.clone(), &new_claim)?;
+
+    update_cohort(
+        storage,
+        &config,
+        &mut state,
+        claim.init_vesting.seconds(),
+        claim.amount,
+        new_claim.amount,
+        env.block.time
+    )?;
+
+    let (old_plus, old_minus) = periodic_offset(claim);
+    let (new_plus, new_minus) = periodic_offset(&new_claim);
+
+    state.periodic_offset_plus = state.periodic_offset_plus - old_plus + new_plus;
+    state.periodic_offset_minus = state.periodic_offset_minus - old_minus + new_minus;
+
+    if increase {
+        state.total_vested += plan.applied;
+    } else {
+        state.total_vested -= plan.applied;
+    }
+
+    if is_fully_claimed && !was_fully_claimed {
+        state.fully_claimed_count += 1;
+    } else if was_fully_claimed && !is_fully_claimed {
+        state.fully_claimed_count -= 1;
+    }
+
+    STATE.save(storage, &state)?;
+
+    update_voting_power(storage, env.block.height, user)
+}
+
+pub fn set_early_unlock(
+    deps: DepsMut,
+    info: MessageInfo,
+    early_unlock: Option<EarlyUnlockConfig>
+) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
+    check_is_admin(deps.as_ref(), info.sender)?;
+
+    if let Some(early_unlock) = &early_unlock {
+        validate_early_unlock(early_unlock)?;
+    }
+
+    let mut config = CONFIG.load(deps.storage)?;
+    let old_value = early_unlock_value(&config.early_unlock)?;
+
+    config.early_unlock = early_unlock;
+
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(
+        Response::new()
+            .add_event(
+                config_changed_event(
+                    "early_unlock",
+                    old_value,
+                    early_unlock_value(&config.early_unlock)?
+                )
+            )
+            .add_attribute("method", "set_early_unlock")
+    )
+}
+
+/// Releases `amount` of the sender's locked tokens right away. The amount is taken off the
+/// periods that have not unlocked yet the same way DecreaseGrant does, the penalty is kept
+/// from it and routed to the configured destination.
+pub fn early_unlock(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint256
+) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let early_unlock = config.early_unlock.clone().ok_or(ContractError::EarlyUnlockDisabled {})?;
+
+    if !info.funds.is_empty() {
+        return Err(ContractError::InvalidFunds {});
+    }
+
+    if amount == Uint256::zero() {
+        return Err(ContractError::InvalidInjxAmount {});
+    }
+
+    if config.grant_nft.is_some() && GRANT_IDS.has(deps.storage, info.sender.clone()) {
+        return Err(ContractError::GrantIsTokenized {});
+    }
+
+    let user = info.sender;
+
+    let claim = CLAIMABLE_AMOUNT.may_load(deps.storage, user.clone())?.ok_or(
+        ContractError::CannotClaim {}
+    )?;
+    let instant = INSTANT_CLAIMABLE_AMOUNT.may_load(
+        deps.storage,
+        user.clone()
+    )?.unwrap_or_default();
+
+    let quote = quote_early_unlock(
+        &config,
+        &early_unlock,
+        instant,
+        &claim,
+        env.block.time,
+        amount
+    )?;
+
+    let outgoing = match &early_unlock.destination {
+        PenaltyDestination::Burn => quote.released + quote.penalty,
+        PenaltyDestination::Treasury => {
+            if config.treasury.is_none() {
+                return Err(ContractError::TreasuryNotSet {});
+            }
+
+            quote.released + quote.penalty
+        }
+        PenaltyDestination::Redistribute => quote.released,
+    };
+
+    if config.mint_cap.is_none() {
+        let balance = deps.querier.query_balance(
+            env.contract.address.to_string(),
+            config.injex_token.clone()
+        )?;
+
+        if Uint256::from_uint128(balance.amount) < outgoing {
+            return Err(ContractError::InsufficientContractBalance {});
+        }
+    }
+
+    apply_rebase(deps.storage, &env, &user, instant, &claim, &quote.plan, false)?;
+
+    let mut msgs: Vec<CosmosMsg<InjectiveMsgWrapper>> = vec![];
+
+    if quote.released != Uint256::zero() {
+        let released = Coin {
+            amount: Uint128::from_str(&quote.released.to_string())?,
+            denom: config.injex_token.clone(),
+        };
+
+        msgs.extend(payout_msgs(deps.storage, &env, &config, &user, vec![released])?);
+    }
+
+    if quote.penalty != Uint256::zero() {
+        let penalty = Coin {
+            amount: Uint128::from_str(&quote.penalty.to_string())?,
+            denom: config.injex_token.clone(),
+        };
+
+        match &early_unlock.destination {
+            // in mint mode the penalty is simply never minted
+            PenaltyDestination::Burn if config.mint_cap.is_none() => {
+                msgs.push(CosmosMsg::Bank(BankMsg::Burn { amount: vec![penalty] }));
+            }
+            PenaltyDestination::Burn => {}
+            PenaltyDestination::Treasury => {
+                let treasury = config.treasury.clone().ok_or(ContractError::TreasuryNotSet {})?;
+
+                msgs.extend(payout_msgs(deps.storage, &env, &config, &treasury, vec![penalty])?);
+            }
+            PenaltyDestination::Redistribute => {
+                distribute_bonus(deps.storage, &user, quote.penalty, env.block.time)?;
+            }
+        }
+    }
+
+    Ok(
+        Response::new()
+            .add_messages(msgs)
+            .add_event(
+                Event::new("early_unlock")
+                    .add_attribute("beneficiary", user.clone())
+                    .add_attribute("requested_amount", amount)
+                    .add_attribute("unlocked_amount", quote.plan.applied)
+                    .add_attribute("penalty", quote.penalty)
+                    .add_attribute("released", quote.released)
+                    .add_attribute("destination", penalty_destination_value(&early_unlock)?)
+                    .add_attribute("amount_per_period", quote.plan.new_claim.amount)
+            )
+            .add_attribute("user", user)
+            .add_attribute("method", "early_unlock")
+    )
+}
+
+struct EarlyUnlock {
+    plan: RebasePlan,
+    penalty: Uint256,
+    released: Uint256,
+}
+
+// The penalty is max_penalty_percents of what is unlocked, scaled by the share of the whole
+// lock duration still ahead of the released periods on average, so it decays linearly to zero
+// as the schedule approaches its end
+fn quote_early_unlock(
+    config: &Config,
+    early_unlock: &EarlyUnlockConfig,
+    instant: Uint256,
+    claim: &UserClaims,
+    time: Timestamp,
+    amount: Uint256
+) -> Result<EarlyUnlock, ContractError> {
+    let plan = plan_rebase(config, instant, claim, time, Some(amount), false)?;
+
+    let lock_seconds = config.lock_minutes * Uint256::from(60_u64);
+    let lock_duration = lock_seconds * config.lock_periods;
+
+    // periods left unlock at init_vesting + lock_seconds * (periods_passed + 1 ..= lock_periods),
+    // twice their average time to unlock is what is computed here
+    let remaining_x2 = (
+        Uint256::from(claim.init_vesting.seconds() * 2) +
+        lock_seconds * (Uint256::from(plan.periods_passed + 1) + config.lock_periods)
+    )
+        .saturating_sub(Uint256::from(time.seconds() * 2))
+        .min(lock_duration * Uint256::from(2_u64));
+
+    let penalty =
+        (plan.applied * early_unlock.max_penalty_percents * remaining_x2) /
+        (PERCENTS * lock_duration * Uint256::from(2_u64));
+
+    Ok(EarlyUnlock {
+        released: plan.applied - penalty,
+        plan,
+        penalty,
+    })
+}
+
+pub fn register_merkle_root(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    merkle_root: String,
+    total_amount: Uint256
+) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
+    check_is_admin(deps.as_ref(), info.sender)?;
+
+    if MERKLE_ALLOCATION.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::MerkleRootAlreadySet {});
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+
+    let mut root_buf: [u8; 32] = [0; 32];
+    hex::decode_to_slice(&merkle_root, &mut root_buf).map_err(
+        |_| ContractError::InvalidMerkleRoot {}
+    )?;
+
+    if total_amount == Uint256::zero() {
+        return Err(ContractError::InvalidInjxAmount {});
+    }
+
+    // Attached funds are deposited into the pool before the root is allocated from it, like
+    // with VestTokens. Proposals carry no funds and rely on the pool alone
+    if info.funds.iter().any(|coin| coin.denom != config.injex_token) {
+        return Err(ContractError::InvalidCoin {});
+    }
+
+    // In mint mode the root is an obligation under the mint cap like any other grant
+    if config.mint_cap.is_some() && !info.funds.is_empty() {
+        return Err(ContractError::InvalidFunds {});
+    }
+
+    check_allocation_cap(deps.as_ref(), &config, total_amount)?;
+    check_unallocated(deps.as_ref(), &env, &config, total_amount)?;
+
+    let allocation = MerkleAllocation {
+        merkle_root: merkle_root.clone(),
+        total_amount,
+        registered_amount: Uint256::zero(),
+    };
+
+    MERKLE_ALLOCATION.save(deps.storage, &allocation)?;
+
+    Ok(
+        Response::new()
+            .add_event(
+                Event::new("merkle_root_registered")
+                    .add_attribute("merkle_root", merkle_root.clone())
+                    .add_attribute("total_amount", allocation.total_amount)
+            )
+            .add_attribute("method", "register_merkle_root")
+            .add_attribute("merkle_root", merkle_root)
+            .add_attribute("total_amount", allocation.total_amount)
+    )
+}
+
+pub fn register_vesting(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint256,
+    vesting_start: Timestamp,
+    proof: Vec<String>
+) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
+    let sender = info.sender;
+    let mut allocation = MERKLE_ALLOCATION.may_load(deps.storage)?.ok_or(
+        ContractError::MerkleRootNotSet {}
+    )?;
+
+    if MERKLE_REGISTERED.has(deps.storage, sender.clone()) {
+        return Err(ContractError::AlreadyRegistered {});
+    }
+
+    if amount == Uint256::zero() {
+        return Err(ContractError::InvalidInjxAmount {});
+    }
+
+    verify_merkle_proof(&allocation.merkle_root, &sender, amount, vesting_start, proof)?;
+    check_no_position(deps.storage, &sender)?;
+
+    if allocation.registered_amount + amount > allocation.total_amount {
+        return Err(ContractError::MerkleAllocationExceeded {});
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+
+    let (event, mint_msg) = create_vesting(
+        deps.storage,
+        &config,
+        &sender,
+        amount,
+        vesting_start,
+        &env.block,
+        "merkle"
+    )?;
+
+    allocation.registered_amount += amount;
+
+    MERKLE_ALLOCATION.save(deps.storage, &allocation)?;
+    MERKLE_REGISTERED.save(deps.storage, sender.clone(), &true)?;
+
+    STATE.update(
+        deps.storage,
+        |mut state| -> Result<State, StdError> {
+            state.total_vested += amount;
+
+            Ok(state)
+        }
+    )?;
+
+    Ok(
+        Response::new()
+            .add_messages(mint_msg)
+            .add_event(event)
+            .add_attribute("method", "register_vesting")
+            .add_attribute("user", sender)
+            .add_attribute("amount", amount)
+    )
+}
+
+pub fn redeem_voucher(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    voucher: Voucher,
+    signature: Binary
+) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let signer = config.voucher_signer.clone().ok_or(ContractError::VoucherSignerNotSet {})?;
+
+    if voucher.contract != env.contract.address || voucher.beneficiary != info.sender {
+        return Err(ContractError::InvalidVoucher {});
+    }
+
+    if voucher.amount == Uint256::zero() {
+        return Err(ContractError::InvalidInjxAmount {});
+    }
+
+    if voucher.expires <= env.block.time {
+        return Err(ContractError::VoucherExpired {});
+    }
+
+    if USED_VOUCHER_NONCES.has(deps.storage, voucher.nonce) {
+        return Err(ContractError::VoucherNonceUsed {});
+    }
+
+    let message_hash = sha2::Sha256::digest(to_json_vec(&voucher)?);
+
+    let is_valid = (match &signer {
+        VoucherSigner::Secp256k1 { public_key } =>
+            deps.api.secp256k1_verify(&message_hash, &signature, public_key),
+        VoucherSigner::Ed25519 { public_key } =>
+            deps.api.ed25519_verify(&message_hash, &signature, public_key),
+    }).map_err(|_| ContractError::InvalidVoucherSignature {})?;
+
+    if !is_valid {
+        return Err(ContractError::InvalidVoucherSignature {});
+    }
+
+    check_no_position(deps.storage, &info.sender)?;
+
+    // Vouchers are backed by tokens deposited to the contract beforehand
+    check_allocation_cap(deps.as_ref(), &config, voucher.amount)?;
+    check_unallocated(deps.as_ref(), &env, &config, voucher.amount)?;
+
+    let (event, mint_msg) = create_vesting(
+        deps.storage,
+        &config,
+        &info.sender,
+        voucher.amount,
+        voucher.vesting_start,
+        &env.block,
+        "voucher"
+    )?;
+
+    USED_VOUCHER_NONCES.save(deps.storage, voucher.nonce, &true)?;
+
+    STATE.update(
+        deps.storage,
+        |mut state| -> Result<State, StdError> {
+            state.total_vested += voucher.amount;
+
+            Ok(state)
+        }
+    )?;
+
+    Ok(
+        Response::new()
+            .add_messages(mint_msg)
+            .add_event(event.add_attribute("nonce", voucher.nonce.to_string()))
+            .add_attribute("method", "redeem_voucher")
+            .add_attribute("user", info.sender)
+            .add_attribute("amount", voucher.amount)
+            .add_attribute("nonce", voucher.nonce.to_string())
+    )
+}
+
+pub fn claim(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo
+) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Tokenized grants can only be claimed by whoever holds the grant NFT
+    if config.grant_nft.is_some() && GRANT_IDS.has(deps.storage, info.sender.clone()) {
+        return Err(ContractError::GrantIsTokenized {});
+    }
+
+    claim_position(deps, env, info.sender.clone(), info.sender)
+}
+
+pub fn claim_grant(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    grant_id: u64
+) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
+    let position = grant_position(deps.as_ref(), grant_id, &info.sender)?;
+
+    claim_position(deps, env, position, info.sender)
+}
+
+// Position of a tokenized grant, as long as `sender` holds its NFT
+fn grant_position(deps: Deps, grant_id: u64, sender: &Addr) -> Result<Addr, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let nft_contract = config.grant_nft.ok_or(ContractError::GrantNftNotSet {})?;
+
+    let position = GRANTS.may_load(deps.storage, grant_id)?.ok_or(
+        ContractError::GrantNotFound {}
+    )?;
+
+    let owner: OwnerOfResponse = deps.querier.query_wasm_smart(
+        nft_contract,
+        &(cw721_base::QueryMsg::<Empty>::OwnerOf {
+            token_id: grant_id.to_string(),
+            include_expired: None,
+        })
+    )?;
+
+    if owner.owner != *sender {
+        return Err(ContractError::NotGrantOwner {});
+    }
+
+    Ok(position)
+}
+
+pub fn set_reward_denoms(
+    deps: DepsMut,
+    info: MessageInfo,
+    denoms: Vec<String>
+) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
+    check_is_admin(deps.as_ref(), info.sender)?;
+
+    validate_reward_denoms(&denoms)?;
+
+    let mut config = CONFIG.load(deps.storage)?;
+
+    // Rewards already distributed in a removed denom stay claimable
+    let old_denoms = config.reward_denoms.join(",");
+
+    config.reward_denoms = denoms;
+
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(
+        Response::new()
+            .add_event(
+                config_changed_event("reward_denoms", old_denoms, config.reward_denoms.join(","))
+            )
+            .add_attribute("method", "set_reward_denoms")
+    )
+}
+
+/// Anyone can distribute, e.g. the treasury routing a share of the protocol fees. Rewards
+/// are shared pro-rata to the balance every position has locked at the time
+pub fn distribute_rewards(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo
+) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if info.funds.is_empty() {
+        return Err(ContractError::InvalidFunds {});
+    }
+
+    if info.funds.iter().any(|coin| !config.reward_denoms.contains(&coin.denom)) {
+        return Err(ContractError::InvalidCoin {});
+    }
+
+    let mut state = STATE.load(deps.storage)?;
+
+    release_unlocked(deps.storage, &mut state, env.block.time)?;
+
+    let total_weight = state.total_locked;
+
+    if total_weight == Uint256::zero() {
+        return Err(ContractError::NoRewardWeight {});
+    }
+
+    for coin in &info.funds {
+        add_rewards(
+            deps.storage,
+            &mut state,
+            &coin.denom,
+            Uint256::from_uint128(coin.amount),
+            total_weight,
+            env.block.time
+        )?;
+    }
+
+    STATE.save(deps.storage, &state)?;
+
+    Ok(
+        Response::new()
+            .add_event(
+                Event::new("rewards_distributed")
+                    .add_attribute("sender", info.sender.clone())
+                    .add_attribute("amounts", coins_value(&info.funds))
+                    .add_attribute("total_weight", total_weight)
+            )
+            .add_attribute("method", "distribute_rewards")
+    )
+}
+
+pub fn claim_rewards(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    grant_id: Option<u64>
+) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let position = match grant_id {
+        Some(grant_id) => grant_position(deps.as_ref(), grant_id, &info.sender)?,
+        None => {
+            // Tokenized grants can only be claimed by whoever holds the grant NFT
+            if config.grant_nft.is_some() && GRANT_IDS.has(deps.storage, info.sender.clone()) {
+                return Err(ContractError::GrantIsTokenized {});
+            }
+
+            info.sender.clone()
+        }
+    };
+
+    let claim = CLAIMABLE_AMOUNT.may_load(deps.storage, position.clone())?.unwrap_or_default();
+    let mut state = STATE.load(deps.storage)?;
+
+    let rewards = pending_rewards(
+        deps.storage,
+        &state,
+        &config,
+        &position,
+        &claim,
+        env.block.time
+    )?;
+
+    if rewards.is_empty() {
+        return Err(ContractError::CannotClaim {});
+    }
+
+    for totals in &state.reward_totals {
+        REWARD_CHECKPOINTS.save(
+            deps.storage,
+            (position.clone(), totals.denom.clone()),
+            &(RewardCheckpoint {
+                index: totals.index,
+                pending: Uint256::zero(),
+                time: env.block.time,
+            })
+        )?;
+    }
+
+    for coin in &rewards {
+        state.reward_totals(&coin.denom).total_claimed += Uint256::from_uint128(coin.amount);
+    }
+
+    STATE.save(deps.storage, &state)?;
+
+    Ok(
+        Response::new()
+            .add_message(BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: rewards.clone(),
+            })
+            .add_event(
+                Event::new("rewards_claimed")
+                    .add_attribute("beneficiary", position)
+                    .add_attribute("recipient", info.sender)
+                    .add_attribute("amounts", coins_value(&rewards))
+            )
+            .add_attribute("method", "claim_rewards")
+    )
+}
+
+fn claim_position(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    recipient: Addr
+) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
+    let config = CONFIG.load(deps.storage).unwrap();
+
+    let instant_claim = INSTANT_CLAIMABLE_AMOUNT.load(deps.storage, sender.clone()).unwrap_or(
+        Uint256::zero()
+    );
+
+    let claim = CLAIMABLE_AMOUNT.may_load(deps.storage, sender.clone())?.unwrap_or_default();
+
+    let curr_time = env.block.time;
+
+    let reward = position_at(&config, instant_claim, &claim, curr_time)?.periodic_claimable;
+    let bonus = pending_bonus(
+        deps.storage,
+        &STATE.load(deps.storage)?,
+        &config,
+        &sender,
+        &claim,
+        curr_time
+    )?;
+
+    // Extra denoms are released on the schedule of the main position
+    let mut extra_claims: Vec<(String, DenomClaims, Uint256)> = vec![];
+
+    for item in EXTRA_CLAIMS.prefix(sender.clone()).range(
+        deps.storage,
+        None,
+        None,
+        Order::Ascending
+    ) {
+        let (denom, denom_claim) = item?;
+
+        let denom_reward = position_at(
+            &config,
+            denom_claim.instant_claimable,
+            &denom_schedule(&claim, &denom_claim),
+            curr_time
+        )?.periodic_claimable;
+
+        if denom_reward != Uint256::zero() || denom_claim.instant_claimable != Uint256::zero() {
+            extra_claims.push((denom, denom_claim, denom_reward));
+        }
+    }
+
+    if
+        reward == Uint256::zero() &&
+        instant_claim == Uint256::zero() &&
+        bonus == Uint256::zero() &&
+        extra_claims.is_empty()
+    {
+        return Err(ContractError::CannotClaim {});
+    }
+
+    let mut send_amount: Vec<Coin> = vec![];
+
+    if reward + instant_claim + bonus != Uint256::zero() {
+        send_amount.push(Coin {
+            amount: Uint128::from_str(&(reward + instant_claim + bonus).to_string())?,
+            denom: config.injex_token.to_string(),
+        });
+    }
+
+    for (denom, denom_claim, denom_reward) in &extra_claims {
+        send_amount.push(Coin {
+            amount: Uint128::from_str(
+                &(*denom_reward + denom_claim.instant_claimable).to_string()
+            )?,
+            denom: denom.clone(),
+        });
+    }
+
+    for coin in &send_amount {
+        if config.mint_cap.is_some() && coin.denom == config.injex_token {
+            continue;
+        }
+
+        let balance = deps.querier.query_balance(
+            env.contract.address.to_string(),
+            coin.denom.clone()
+        )?;
+
+        if balance.amount < coin.amount {
+            return Err(ContractError::InsufficientContractBalance {});
+        }
+    }
+
+    let extra_sent: Vec<Coin> = send_amount
+        .iter()
+        .filter(|coin| coin.denom != config.injex_token)
+        .cloned()
+        .collect();
+
+    let payout_msgs = payout_msgs(deps.storage, &env, &config, &recipient, send_amount)?;
+
+    for (denom, mut denom_claim, denom_reward) in extra_claims {
+        denom_claim.amount_claimed += denom_reward;
+        denom_claim.instant_claimable = Uint256::zero();
+
+        EXTRA_CLAIMS.save(deps.storage, (sender.clone(), denom), &denom_claim)?;
+    }
+
+    if instant_claim != Uint256::zero() {
+        INSTANT_CLAIMABLE_AMOUNT.update(
+            deps.storage,
+            sender.clone(),
+            |_| -> Result<Uint256, StdError> { Ok(Uint256::zero()) }
+        )?;
+    }
+
+    let state = STATE.load(deps.storage)?;
+
+    let new_claim = UserClaims {
+        last_claimed: curr_time,
+        amount_claimed: claim.amount_claimed + reward,
+        ..claim.clone()
+    };
+
+    checkpoint_rewards(deps.storage, &state, &config, &sender, &claim, curr_time)?;
+
+    // the bonus is paid out with the claim
+    BONUS_CHECKPOINTS.save(
+        deps.storage,
+        sender.clone(),
+        &(RewardCheckpoint {
+            index: state.bonus_index,
+            pending: Uint256::zero(),
+            time: curr_time,
+        })
+    )?;
+
+    CLAIMABLE_AMOUNT.save(deps.storage, sender.clone(), &new_claim)?;
+
+    update_voting_power(deps.storage, env.block.height, &sender)?;
+
+    // extras and bonus can still be paid out once the position is done, count it only once
+    let total = total_periodic(&config, &claim);
+    let was_fully_claimed = instant_claim == Uint256::zero() && claim.amount_claimed == total;
+    let is_fully_claimed = claim.amount_claimed + reward == total;
+
+    STATE.update(
+        deps.storage,
+        |mut state| -> Result<State, StdError> {
+            state.total_claimed += reward + instant_claim + bonus;
+            state.instant_outstanding -= instant_claim;
+            state.periodic_claimed += reward;
+            state.bonus_outstanding -= bonus;
+
+            if is_fully_claimed && !was_fully_claimed {
+                state.fully_claimed_count += 1;
+            }
+
+            for coin in &extra_sent {
+                state.denom_totals(&coin.denom).total_claimed += Uint256::from_uint128(
+                    coin.amount
+                );
+            }
+
+            Ok(state)
+        }
+    ).unwrap();
+
+    let claim_id = CLAIM_COUNT.may_load(deps.storage, sender.clone())?.unwrap_or_default();
+
+    CLAIM_HISTORY.save(
+        deps.storage,
+        (sender.clone(), claim_id),
+        &(ClaimRecord {
+            id: claim_id,
+            timestamp: curr_time,
+            height: env.block.height,
+            instant_amount: instant_claim,
+            periodic_amount: reward,
+            recipient: recipient.clone(),
+            extra: extra_sent.clone(),
+            bonus_amount: bonus,
+        })
+    )?;
+    CLAIM_COUNT.save(deps.storage, sender.clone(), &(claim_id + 1))?;
+
+    let grant_id = GRANT_IDS.may_load(deps.storage, sender.clone())?;
+
+    Ok(
+        Response::new()
+            .add_messages(payout_msgs)
+            .add_event(
+                Event::new("claim")
+                    .add_attribute("beneficiary", sender.clone())
+                    .add_attribute("recipient", recipient.clone())
+                    .add_attribute("grant_id", grant_id.unwrap_or_default().to_string())
+                    .add_attribute("instant_amount", instant_claim)
+                    .add_attribute("periodic_amount", reward)
+                    .add_attribute("bonus_amount", bonus)
+                    .add_attribute("amount", reward + instant_claim + bonus)
+                    .add_attribute("total_periodic_claimed", claim.amount_claimed + reward)
+                    .add_attribute("extra_amounts", coins_value(&extra_sent))
+            )
+            .add_attribute("user", sender.clone())
+            .add_attribute("recipient", recipient)
+            .add_attribute("amount_claimed", reward + instant_claim + bonus)
+            .add_attribute("method", "execute_claim")
+    )
+}
+
+pub fn query_claimable_amount(deps: Deps, env: Env, addr: String) -> StdResult<Uint256> {
+    let user = Addr::unchecked(addr);
+    let config = CONFIG.load(deps.storage).unwrap();
+
+    let instant_claim = INSTANT_CLAIMABLE_AMOUNT.load(deps.storage, user.clone()).unwrap_or(
+        Uint256::zero()
+    );
+
+    let claim = CLAIMABLE_AMOUNT.may_load(deps.storage, user.clone())?.unwrap_or_default();
+
+    let position = position_at(&config, instant_claim, &claim, env.block.time)?;
+    let bonus = pending_bonus(
+        deps.storage,
+        &STATE.load(deps.storage)?,
+        &config,
+        &user,
+        &claim,
+        env.block.time
+    )?;
+
+    Ok(position.periodic_claimable + position.instant_claimable + bonus)
+}
+
+pub fn query_extra_claimable(deps: Deps, env: Env, addr: String) -> StdResult<Vec<Coin>> {
+    let user = Addr::unchecked(addr);
+    let config = CONFIG.load(deps.storage)?;
+
+    let claim = CLAIMABLE_AMOUNT.may_load(deps.storage, user.clone())?.unwrap_or_default();
+
+    EXTRA_CLAIMS.prefix(user)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (denom, denom_claim) = item?;
+
+            let position = position_at(
+                &config,
+                denom_claim.instant_claimable,
+                &denom_schedule(&claim, &denom_claim),
+                env.block.time
+            )?;
+
+            Ok(Coin {
+                amount: Uint128::from_str(
+                    &(position.periodic_claimable + position.instant_claimable).to_string()
+                )?,
+                denom,
+            })
+        })
+        .collect()
+}
+
+pub fn query_simulate_at(
+    deps: Deps,
+    addr: String,
+    timestamp: Timestamp
+) -> StdResult<SimulateAtResponse> {
+    let user = Addr::unchecked(addr);
+    let config = CONFIG.load(deps.storage)?;
+
+    let instant_claim = INSTANT_CLAIMABLE_AMOUNT.may_load(
+        deps.storage,
+        user.clone()
+    )?.unwrap_or_default();
+    let claim = CLAIMABLE_AMOUNT.may_load(deps.storage, user.clone())?.unwrap_or_default();
+
+    let position = position_at(&config, instant_claim, &claim, timestamp)?;
+    let bonus = pending_bonus(
+        deps.storage,
+        &STATE.load(deps.storage)?,
+        &config,
+        &user,
+        &claim,
+        timestamp
+    )?;
+
+    Ok(SimulateAtResponse {
+        timestamp,
+        claimable: position.instant_claimable + position.periodic_claimable + bonus,
+        instant_claimable: position.instant_claimable,
+        periodic_claimable: position.periodic_claimable,
+        bonus,
+        vested: position.vested,
+        locked: position.locked,
+        next_unlock: position.next_unlock,
+    })
+}
+
+pub fn query_total_claimed(deps: Deps) -> StdResult<Uint256> {
+    let state = STATE.load(deps.storage)?;
+
+    Ok(state.total_claimed)
+}
+
+pub fn query_instant_amount(deps: Deps, addr: String) -> StdResult<Uint256> {
+    let amount = INSTANT_CLAIMABLE_AMOUNT.load(deps.storage, Addr::unchecked(addr)).unwrap();
+
+    Ok(amount)
+}
+
+pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let admin = ADMIN.load(deps.storage)?;
+
+    Ok(ConfigResponse {
+        admin,
+        injex_token: config.injex_token,
+        instant_claim_percents: config.instant_claim_percents,
+        lock_minutes: config.lock_minutes,
+        lock_periods: config.lock_periods,
+        voucher_signer: config.voucher_signer,
+        grant_nft: config.grant_nft,
+        extra_denoms: config.extra_denoms,
+        max_total_allocation: config.max_total_allocation,
+        treasury: config.treasury,
+        mint_cap: config.mint_cap,
+        early_unlock: config.early_unlock,
+        reward_denoms: config.reward_denoms,
+        min_delay: config.min_delay,
+        approvals: config.approvals,
+    })
+}
+
+pub fn query_user_vesting_info(deps: Deps, env: Env, addr: String) -> StdResult<UserVestingInfo> {
+    let user = Addr::unchecked(addr);
+    let config = CONFIG.load(deps.storage).unwrap();
+    let vesting = CLAIMABLE_AMOUNT.load(deps.storage, user.clone()).unwrap();
+    let instant = INSTANT_CLAIMABLE_AMOUNT.load(deps.storage, user.clone()).unwrap();
+
+    let lock_minutes = u64::from_str(&config.lock_minutes.to_string()).unwrap();
+    let remaining_reward = total_periodic(&config, &vesting) - vesting.amount_claimed;
+
+    let curr_time = env.block.time.seconds();
+    let time_passed = curr_time.saturating_sub(vesting.init_vesting.seconds()) / 60;
+    let periods: u64 = periods_claimed(&vesting)?;
+
+    let next_claim = if
+        vesting.amount_claimed < vesting.base_vested ||
+        time_passed.saturating_sub(periods * lock_minutes) >= lock_minutes
+    {
+        Uint256::zero()
+    } else {
+        Uint256::from_u128(
+            (vesting.init_vesting.seconds() + lock_minutes * (periods + 1) * 60).into()
+        )
+    };
+
+    Ok(UserVestingInfo {
+        full_amount: remaining_reward + instant,
+        next_claim,
+        claimed: vesting.amount_claimed,
+    })
+}
+
+pub fn query_total_vested(deps: Deps) -> StdResult<Uint256> {
+    let state = STATE.load(deps.storage)?;
+
+    Ok(state.total_vested)
+}
+
+pub fn query_totals(deps: Deps) -> StdResult<TotalsResponse> {
+    let state = STATE.load(deps.storage)?;
+
+    Ok(TotalsResponse {
+        total_claimed: state.total_claimed,
+        total_vested: state.total_vested,
+        extra: state.extra_totals,
+    })
+}
+
+pub fn query_unallocated(deps: Deps, env: Env) -> StdResult<UnallocatedResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let total_allocated = total_allocated(deps.storage)?;
+
+    let denoms = std::iter::once(&config.injex_token).chain(config.extra_denoms.iter());
+
+    let unallocated = denoms
+        .map(|denom| {
+            Ok(Coin {
+                amount: Uint128::from_str(
+                    &unallocated_amount(deps, &env, denom)?.to_string()
+                )?,
+                denom: denom.clone(),
+            })
+        })
+        .collect::<StdResult<Vec<Coin>>>()?;
+
+    Ok(UnallocatedResponse {
+        unallocated,
+        total_allocated,
+        max_total_allocation: config.max_total_allocation,
+        remaining_allocation: [config.max_total_allocation, config.mint_cap]
+            .into_iter()
+            .flatten()
+            .min()
+            .map(|cap| cap.saturating_sub(total_allocated)),
+    })
+}
+
+pub fn query_stats(deps: Deps, env: Env) -> StdResult<StatsResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let state = STATE.load(deps.storage)?;
+
+    let lock_minutes = Uint64::try_from(config.lock_minutes)?.u64();
+    let lock_periods = Uint64::try_from(config.lock_periods)?.u64();
+
+    let mut periodic_unlocked = Uint256::zero();
+    let mut periodic_total = Uint256::zero();
+
+    for cohort in VESTING_COHORTS.range(deps.storage, None, None, Order::Ascending) {
+        let (start, amount_per_period) = cohort?;
+
+        let periods_passed = (
+            env.block.time.seconds().saturating_sub(start) /
+            60 /
+            lock_minutes
+        ).min(lock_periods);
+
+        periodic_unlocked += amount_per_period * Uint256::from(periods_passed);
+        periodic_total += amount_per_period * config.lock_periods;
+    }
+
+    let balance = deps.querier.query_balance(
+        env.contract.address,
+        config.injex_token.clone()
+    )?;
+    let balance = Uint256::from_uint128(balance.amount);
+
+    let unregistered_merkle = match MERKLE_ALLOCATION.may_load(deps.storage)? {
+        Some(allocation) => allocation.total_amount - allocation.registered_amount,
+        None => Uint256::zero(),
+    };
+
+    let mintable = mintable_amount(&config, &state);
+    let backing = balance + mintable;
+
+    periodic_unlocked =
+        periodic_unlocked + state.periodic_offset_plus - state.periodic_offset_minus;
+    periodic_total = periodic_total + state.periodic_offset_plus - state.periodic_offset_minus;
+
+    let unlocked_unclaimed = periodic_unlocked - state.periodic_claimed;
+    let locked = periodic_total - periodic_unlocked;
+    let liabilities =
+        state.instant_outstanding +
+        unlocked_unclaimed +
+        locked +
+        state.bonus_outstanding +
+        state.rewards_outstanding(&config.injex_token) +
+        unregistered_merkle;
+
+    Ok(StatsResponse {
+        beneficiary_count: state.beneficiary_count,
+        fully_claimed_count: state.fully_claimed_count,
+        total_vested: state.total_vested,
+        total_claimed: state.total_claimed,
+        unlocked_unclaimed,
+        locked,
+        instant_liability: state.instant_outstanding,
+        balance,
+        mintable,
+        surplus: backing.saturating_sub(liabilities),
+        deficit: liabilities.saturating_sub(backing),
+    })
+}
+
+pub fn query_check_solvency(deps: Deps, env: Env) -> StdResult<SolvencyResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let state = STATE.load(deps.storage)?;
+
+    let balance = deps.querier.query_balance(
+        env.contract.address,
+        config.injex_token.clone()
+    )?;
+    let balance = Uint256::from_uint128(balance.amount);
+
+    let outstanding = state.total_vested - state.total_claimed;
+    let unregistered_merkle = match MERKLE_ALLOCATION.may_load(deps.storage)? {
+        Some(allocation) => allocation.total_amount - allocation.registered_amount,
+        None => Uint256::zero(),
+    };
+    // rewards distributed in the vesting denom are owed as well
+    let required =
+        outstanding + unregistered_merkle + state.rewards_outstanding(&config.injex_token);
+    let mintable = mintable_amount(&config, &state);
+    let backing = balance + mintable;
+
+    Ok(SolvencyResponse {
+        balance,
+        mintable,
+        outstanding,
+        unregistered_merkle,
+        solvent: backing >= required,
+        shortfall: required.saturating_sub(backing),
+    })
+}
+
+// What can still be minted to pay claims, zero outside of mint mode
+fn mintable_amount(config: &Config, state: &State) -> Uint256 {
+    config.mint_cap.map_or(Uint256::zero(), |cap| -> Uint256 {
+        cap.saturating_sub(state.total_minted)
+    })
+}
+
+pub fn query_audit_positions(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>
+) -> StdResult<AuditPositionsResponse> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|addr| Bound::exclusive(Addr::unchecked(addr)));
+
+    let positions = CLAIMABLE_AMOUNT.range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (user, claim) = item?;
+            let instant = INSTANT_CLAIMABLE_AMOUNT.may_load(
+                deps.storage,
+                user.clone()
+            )?.unwrap_or_default();
+
+            Ok(audit_position(&config, user, &claim, instant))
+        })
+        .collect::<StdResult<Vec<PositionAudit>>>()?;
+
+    Ok(AuditPositionsResponse { positions })
+}
+
+fn audit_position(
+    config: &Config,
+    user: Addr,
+    claim: &UserClaims,
+    instant: Uint256
+) -> PositionAudit {
+    let mut issues: Vec<PositionIssue> = vec![];
+
+    let periodic_granted = total_periodic(config, claim);
+
+    // amended grants may legitimately end up with nothing left per period
+    if claim.amount == Uint256::zero() {
+        if claim.base_period == 0 {
+            issues.push(PositionIssue::ZeroPeriodAmount);
+        }
+    } else if
+        claim.amount_claimed.saturating_sub(claim.base_vested) % claim.amount != Uint256::zero()
+    {
+        issues.push(PositionIssue::ClaimedNotPeriodMultiple);
+    }
+
+    if claim.amount_claimed > periodic_granted {
+        issues.push(PositionIssue::ClaimedExceedsGrant);
+    }
+
+    if instant > claim.instant_amount && claim.instant_amount != Uint256::zero() {
+        issues.push(PositionIssue::InstantExceedsGrant);
+    }
+
+    let instant_granted = claim.instant_amount.max(instant);
+
+    PositionAudit {
+        user,
+        granted: instant_granted + periodic_granted,
+        claimed: instant_granted - instant + claim.amount_claimed,
+        liability: instant + periodic_granted.saturating_sub(claim.amount_claimed),
+        issues,
+    }
+}
+
+pub fn query_injx_token(deps: Deps) -> StdResult<DenomResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    Ok(DenomResponse { denom: config.injex_token })
+}
+
+pub fn query_unlock_schedule(deps: Deps, addr: String) -> StdResult<UnlockScheduleResponse> {
+    let user = Addr::unchecked(addr);
+    let config = CONFIG.load(deps.storage)?;
+
+    let instant_claim = INSTANT_CLAIMABLE_AMOUNT.may_load(
+        deps.storage,
+        user.clone()
+    )?.unwrap_or_default();
+    let claim = CLAIMABLE_AMOUNT.may_load(deps.storage, user)?.unwrap_or_default();
+
+    let mut tranches: Vec<UnlockTranche> = vec![];
+    let mut cumulative = Uint256::zero();
+
+    let instant_amount = claim.instant_amount.max(instant_claim);
+
+    if instant_amount != Uint256::zero() {
+        cumulative += instant_amount;
+
+        tranches.push(UnlockTranche {
+            timestamp: claim.init_vesting,
+            amount: instant_amount,
+            cumulative,
+            instant: true,
+            claimed: instant_claim == Uint256::zero(),
+        });
+    }
+
+    let lock_minutes = Uint64::try_from(config.lock_minutes)?.u64();
+
+    // Periods unlocked before the last amendment are reported as one tranche
+    if claim.base_vested != Uint256::zero() {
+        cumulative += claim.base_vested;
+
+        tranches.push(UnlockTranche {
+            timestamp: claim.init_vesting.plus_minutes(lock_minutes * claim.base_period),
+            amount: claim.base_vested,
+            cumulative,
+            instant: false,
+            claimed: claim.amount_claimed >= claim.base_vested,
+        });
+    }
+
+    if claim.amount != Uint256::zero() {
+        let lock_periods = Uint64::try_from(config.lock_periods)?.u64();
+        let periods_claimed = periods_claimed(&claim)?;
+
+        for period in claim.base_period + 1..=lock_periods {
+            cumulative += claim.amount;
+
+            tranches.push(UnlockTranche {
+                timestamp: claim.init_vesting.plus_minutes(lock_minutes * period),
+                amount: claim.amount,
+                cumulative,
+                instant: false,
+                claimed: period <= periods_claimed,
+            });
+        }
+    }
+
+    Ok(UnlockScheduleResponse { tranches })
+}
+
+/// Amounts of a position at a given time. Claims, queries and simulations all go through
+/// this so that projections can't drift from what `claim` pays out.
+pub struct PositionAt {
+    pub instant_claimable: Uint256,
+    pub periodic_claimable: Uint256,
+    // everything unlocked so far, claimed or not
+    pub vested: Uint256,
+    pub locked: Uint256,
+    pub next_unlock: Option<Timestamp>,
+}
+
+pub fn position_at(
+    config: &Config,
+    instant_claim: Uint256,
+    claim: &UserClaims,
+    time: Timestamp
+) -> StdResult<PositionAt> {
+    let lock_minutes = Uint64::try_from(config.lock_minutes)?.u64();
+    let lock_periods = Uint64::try_from(config.lock_periods)?.u64();
+    let total_periodic = total_periodic(config, claim);
+
+    let periodic_claimable = if claim.amount_claimed >= total_periodic {
+        Uint256::zero()
+    } else {
+        // Whatever unlocked before the last amendment is fixed, the rest of the schedule
+        // restarts from the period the amendment happened in
+        let base_unclaimed = claim.base_vested.saturating_sub(claim.amount_claimed);
+        let periods = periods_claimed(claim)? - claim.base_period;
+
+        base_unclaimed +
+            calculate_reward_amount(
+                claim.amount,
+                time.seconds(),
+                claim.init_vesting.plus_minutes(lock_minutes * claim.base_period).seconds(),
+                periods,
+                lock_minutes,
+                total_periodic - claim.amount_claimed.max(claim.base_vested)
+            )?
+    };
+
+    let periods_passed =
+        time.seconds().saturating_sub(claim.init_vesting.seconds()) / 60 / lock_minutes;
+
+    let next_unlock = if claim.amount == Uint256::zero() || periods_passed >= lock_periods {
+        None
+    } else {
+        Some(claim.init_vesting.plus_minutes(lock_minutes * (periods_passed + 1)))
+    };
+
+    Ok(PositionAt {
+        instant_claimable: instant_claim,
+        periodic_claimable,
+        vested: claim.instant_amount.max(instant_claim) + claim.amount_claimed + periodic_claimable,
+        locked: total_periodic - claim.amount_claimed - periodic_claimable,
+        next_unlock,
+    })
+}
+
+/// Instant part and amount per period of a new grant. What doesn't divide evenly between the
+/// periods is never unlocked and stays in the contract as dust.
+pub fn split_vesting(
+    amount: Uint256,
+    instant_claim_percents: Uint256,
+    lock_periods: Uint256
+) -> (Uint256, Uint256) {
+    let instant_claim_amount = (amount * instant_claim_percents) / PERCENTS;
+    let amount_per_period = (amount - instant_claim_amount) / lock_periods;
+
+    (instant_claim_amount, amount_per_period)
+}
+
+// Periodic part of a position once every period has unlocked
+pub fn total_periodic(config: &Config, claim: &UserClaims) -> Uint256 {
+    claim.base_vested + claim.amount * (config.lock_periods - Uint256::from(claim.base_period))
+}
+
+// Bonus a position earned up to `time`, forfeits are weighted by locked balance like rewards
+fn pending_bonus(
+    storage: &dyn Storage,
+    state: &State,
+    config: &Config,
+    user: &Addr,
+    claim: &UserClaims,
+    time: Timestamp
+) -> StdResult<Uint256> {
+    let checkpoint = BONUS_CHECKPOINTS.may_load(storage, user.clone())?.unwrap_or_default();
+
+    accrued(config, claim, &checkpoint, state.bonus_index, time, |unlock| {
+        let last = BONUS_INDEX_HISTORY.range(
+            storage,
+            None,
+            Some(Bound::exclusive(unlock.seconds())),
+            Order::Descending
+        )
+            .next()
+            .transpose()?;
+
+        Ok(last.map(|(_, index)| index).unwrap_or_default())
+    })
+}
+
+// Shares `amount` forfeited by `forfeiter` pro-rata to the balance all other positions have
+// locked, O(1) whatever their number. The forfeiter is moved past the bump so it gets no
+// share. Returns false and leaves the amount unallocated when no other position holds anything
+fn distribute_bonus(
+    storage: &mut dyn Storage,
+    forfeiter: &Addr,
+    amount: Uint256,
+    time: Timestamp
+) -> StdResult<bool> {
+    let config = CONFIG.load(storage)?;
+    let mut state = STATE.load(storage)?;
+    let claim = CLAIMABLE_AMOUNT.load(storage, forfeiter.clone())?;
+
+    release_unlocked(storage, &mut state, time)?;
+
+    let forfeiter_locked = position_at(&config, Uint256::zero(), &claim, time)?.locked;
+    let weight = state.total_locked - forfeiter_locked;
+
+    if weight == Uint256::zero() || amount == Uint256::zero() {
+        return Ok(false);
+    }
+
+    // the forfeiter keeps what it earned before, but gets nothing of its own forfeit
+    let pending = pending_bonus(storage, &state, &config, forfeiter, &claim, time)?;
+
+    state.bonus_index += Decimal256::from_ratio(amount, weight);
+    state.bonus_outstanding += amount;
+    state.total_vested += amount;
+
+    BONUS_INDEX_HISTORY.save(storage, time.seconds(), &state.bonus_index)?;
+    BONUS_CHECKPOINTS.save(
+        storage,
+        forfeiter.clone(),
+        &(RewardCheckpoint {
+            index: state.bonus_index,
+            pending,
+            time,
+        })
+    )?;
+    STATE.save(storage, &state)?;
+
+    Ok(true)
+}
+
+// Extra denom counterpart of distribute_bonus, `coin` is paid out as a reward to every other
+// position. Returns false when there is no other position to share it with
+fn distribute_extra(
+    storage: &mut dyn Storage,
+    forfeiter: &Addr,
+    coin: &Coin,
+    time: Timestamp
+) -> StdResult<bool> {
+    let config = CONFIG.load(storage)?;
+    let mut state = STATE.load(storage)?;
+    let claim = CLAIMABLE_AMOUNT.load(storage, forfeiter.clone())?;
+
+    release_unlocked(storage, &mut state, time)?;
+
+    let forfeiter_locked = position_at(&config, Uint256::zero(), &claim, time)?.locked;
+    let weight = state.total_locked - forfeiter_locked;
+
+    if weight == Uint256::zero() || coin.amount.is_zero() {
+        return Ok(false);
+    }
+
+    let key = (forfeiter.clone(), coin.denom.clone());
+    let checkpoint = REWARD_CHECKPOINTS.may_load(storage, key.clone())?.unwrap_or_default();
+
+    // the forfeiter keeps what it earned before, but gets nothing of its own forfeit
+    let pending = accrued_rewards(
+        storage,
+        &config,
+        state.reward_totals(&coin.denom),
+        &claim,
+        &checkpoint,
+        time
+    )?;
+
+    let index = add_rewards(
+        storage,
+        &mut state,
+        &coin.denom,
+        Uint256::from_uint128(coin.amount),
+        weight,
+        time
+    )?;
+
+    REWARD_CHECKPOINTS.save(storage, key, &(RewardCheckpoint { index, pending, time }))?;
+    STATE.save(storage, &state)?;
+
+    Ok(true)
+}
+
+// Bumps the index of `denom` by `amount` shared over `weight` and records it, so that
+// positions can tell later what was distributed between two of their unlocks
+fn add_rewards(
+    storage: &mut dyn Storage,
+    state: &mut State,
+    denom: &str,
+    amount: Uint256,
+    weight: Uint256,
+    time: Timestamp
+) -> StdResult<Decimal256> {
+    let totals = state.reward_totals(denom);
+
+    totals.index += Decimal256::from_ratio(amount, weight);
+    totals.total_distributed += amount;
+
+    REWARD_INDEX_HISTORY.save(storage, (denom.to_string(), time.seconds()), &totals.index)?;
+
+    Ok(totals.index)
+}
+
+// Locked balance of all positions at `time`, the weight rewards are distributed with. Only
+// the unlocks not released yet are looked at, execute paths release them first
+fn total_locked(storage: &dyn Storage, state: &State, time: Timestamp) -> StdResult<Uint256> {
+    let mut locked = state.total_locked;
+
+    for unlock in UNLOCK_SCHEDULE.range(
+        storage,
+        None,
+        Some(Bound::inclusive(time.seconds())),
+        Order::Ascending
+    ) {
+        locked -= unlock?.1;
+    }
+
+    Ok(locked)
+}
+
+// Takes the unlocks that have passed by `time` off State::total_locked. Every unlock is
+// released once, so the work is spread over the calls whatever the number of cohorts
+fn release_unlocked(
+    storage: &mut dyn Storage,
+    state: &mut State,
+    time: Timestamp
+) -> StdResult<()> {
+    let unlocks = UNLOCK_SCHEDULE.range(
+        storage,
+        None,
+        Some(Bound::inclusive(time.seconds())),
+        Order::Ascending
+    ).collect::<StdResult<Vec<(u64, Uint256)>>>()?;
+
+    for (unlock, amount) in unlocks {
+        state.total_locked -= amount;
+        UNLOCK_SCHEDULE.remove(storage, unlock);
+    }
+
+    Ok(())
+}
+
+// Moves the cohort of `start` from `old` to `new` per period, along with the locked balance of
+// its periods still ahead of `time`. Amended positions keep their cohort, `amount` per period
+// from the period they were amended in
+fn update_cohort(
+    storage: &mut dyn Storage,
+    config: &Config,
+    state: &mut State,
+    start: u64,
+    old: Uint256,
+    new: Uint256,
+    time: Timestamp
+) -> StdResult<()> {
+    let lock_minutes = Uint64::try_from(config.lock_minutes)?.u64();
+    let lock_periods = Uint64::try_from(config.lock_periods)?.u64();
+
+    release_unlocked(storage, state, time)?;
+
+    VESTING_COHORTS.update(storage, start, |cohort| -> StdResult<Uint256> {
+        Ok(cohort.unwrap_or_default() + new - old)
+    })?;
+
+    let periods_passed = time.seconds().saturating_sub(start) / 60 / lock_minutes;
+
+    for period in periods_passed + 1..=lock_periods {
+        state.total_locked = state.total_locked + new - old;
+
+        UNLOCK_SCHEDULE.update(
+            storage,
+            start + period * lock_minutes * 60,
+            |amount| -> StdResult<Uint256> { Ok(amount.unwrap_or_default() + new - old) }
+        )?;
+    }
+
+    Ok(())
+}
+
+// Unlock times follow lock_minutes, so changing it schedules every cohort again from scratch
+fn reschedule_unlocks(
+    storage: &mut dyn Storage,
+    config: &Config,
+    time: Timestamp
+) -> StdResult<()> {
+    let mut state = STATE.load(storage)?;
+
+    UNLOCK_SCHEDULE.clear(storage);
+    state.total_locked = Uint256::zero();
+
+    let cohorts = VESTING_COHORTS.range(storage, None, None, Order::Ascending).collect::<
+        StdResult<Vec<(u64, Uint256)>>
+    >()?;
+
+    for (start, amount_per_period) in cohorts {
+        VESTING_COHORTS.remove(storage, start);
+
+        update_cohort(
+            storage,
+            config,
+            &mut state,
+            start,
+            Uint256::zero(),
+            amount_per_period,
+            time
+        )?;
+    }
+
+    STATE.save(storage, &state)
+}
+
+// RewardTotals::index before anything was distributed in the second of `time`
+fn index_before(storage: &dyn Storage, denom: &str, time: Timestamp) -> StdResult<Decimal256> {
+    let last = REWARD_INDEX_HISTORY.prefix(denom.to_string())
+        .range(storage, None, Some(Bound::exclusive(time.seconds())), Order::Descending)
+        .next()
+        .transpose()?;
+
+    Ok(last.map(|(_, index)| index).unwrap_or_default())
+}
+
+// Rewards of one denom a position earned up to `time`
+fn accrued_rewards(
+    storage: &dyn Storage,
+    config: &Config,
+    totals: &RewardTotals,
+    claim: &UserClaims,
+    checkpoint: &RewardCheckpoint,
+    time: Timestamp
+) -> StdResult<Uint256> {
+    accrued(config, claim, checkpoint, totals.index, time, |unlock| {
+        index_before(storage, &totals.denom, unlock)
+    })
+}
+
+// What a position earned from `checkpoint` up to `time` on an index now at `index`, given the
+// index before each second. The locked balance only changes at unlocks, so what was shared is
+// weighted one unlock period after the other, at most lock_periods lookups whatever the
+// number of distributions
+fn accrued(
+    config: &Config,
+    claim: &UserClaims,
+    checkpoint: &RewardCheckpoint,
+    index: Decimal256,
+    time: Timestamp,
+    index_before: impl Fn(Timestamp) -> StdResult<Decimal256>
+) -> StdResult<Uint256> {
+    let mut pending = checkpoint.pending;
+    let mut from = checkpoint.time;
+    let mut from_index = checkpoint.index;
+
+    loop {
+        let position = position_at(config, Uint256::zero(), claim, from)?;
+
+        match position.next_unlock {
+            Some(unlock) if unlock <= time && position.locked != Uint256::zero() => {
+                let unlock_index = index_before(unlock)?;
+
+                pending += position.locked * (unlock_index - from_index);
+                from = unlock;
+                from_index = unlock_index;
+            }
+            _ => {
+                return Ok(pending + position.locked * (index - from_index));
+            }
+        }
+    }
+}
+
+// Settles the rewards `old` earned in every denom and its bonus up to `time`, has to run
+// whenever the schedule of a position changes
+fn checkpoint_rewards(
+    storage: &mut dyn Storage,
+    state: &State,
+    config: &Config,
+    user: &Addr,
+    old: &UserClaims,
+    time: Timestamp
+) -> StdResult<()> {
+    for totals in &state.reward_totals {
+        let key = (user.clone(), totals.denom.clone());
+        let checkpoint = REWARD_CHECKPOINTS.may_load(storage, key.clone())?.unwrap_or_default();
+        let pending = accrued_rewards(storage, config, totals, old, &checkpoint, time)?;
+
+        REWARD_CHECKPOINTS.save(
+            storage,
+            key,
+            &(RewardCheckpoint {
+                index: totals.index,
+                pending,
+                time,
+            })
+        )?;
+    }
+
+    let bonus = pending_bonus(storage, state, config, user, old, time)?;
+
+    BONUS_CHECKPOINTS.save(
+        storage,
+        user.clone(),
+        &(RewardCheckpoint {
+            index: state.bonus_index,
+            pending: bonus,
+            time,
+        })
+    )
+}
+
+// Rewards a position can claim, denoms with nothing to claim are left out
+fn pending_rewards(
+    storage: &dyn Storage,
+    state: &State,
+    config: &Config,
+    user: &Addr,
+    claim: &UserClaims,
+    time: Timestamp
+) -> StdResult<Vec<Coin>> {
+    let mut rewards: Vec<Coin> = vec![];
+
+    for totals in &state.reward_totals {
+        let checkpoint = REWARD_CHECKPOINTS.may_load(
+            storage,
+            (user.clone(), totals.denom.clone())
+        )?.unwrap_or_default();
+
+        let pending = accrued_rewards(storage, config, totals, claim, &checkpoint, time)?;
+
+        if pending != Uint256::zero() {
+            rewards.push(Coin {
+                amount: Uint128::from_str(&pending.to_string())?,
+                denom: totals.denom.clone(),
+            });
+        }
+    }
+
+    Ok(rewards)
+}
+
+// Periods whose unlock was claimed, counted from the vesting start
+fn periods_claimed(claim: &UserClaims) -> StdResult<u64> {
+    if claim.amount == Uint256::zero() {
+        return Ok(claim.base_period);
+    }
+
+    let claimed_after_base = claim.amount_claimed.saturating_sub(claim.base_vested);
+
+    Ok(claim.base_period + Uint64::try_from(claimed_after_base / claim.amount)?.u64())
+}
+
+// base_vested - base_period * amount split by sign, see State
+fn periodic_offset(claim: &UserClaims) -> (Uint256, Uint256) {
+    let base = claim.amount * Uint256::from(claim.base_period);
+
+    (claim.base_vested.saturating_sub(base), base.saturating_sub(claim.base_vested))
+}
+
+pub fn calculate_reward_amount(
+    reward: Uint256,
+    curr_time: u64,
+    instant_time: u64,
+    periods_claimed: u64,
+    period_time: u64,
+    max_reward: Uint256
+) -> StdResult<Uint256> {
+    // Vesting may start in the future for vouchers
+    let minutes_passed = curr_time.saturating_sub(instant_time) / 60;
+
+    let periods_passed = (minutes_passed / period_time).saturating_sub(periods_claimed);
+
+    let mut full_reward = reward * Uint256::from_u128(periods_passed.into());
 
     if full_reward > max_reward {
         full_reward = max_reward;
@@ -450,7 +3086,764 @@ fn calculate_reward_amount(
     Ok(full_reward)
 }
 
-fn check_is_admin(deps: Deps, addr: Addr) -> Result<Response, ContractError> {
+pub fn query_quote_early_unlock(
+    deps: Deps,
+    env: Env,
+    addr: String,
+    amount: Uint256
+) -> StdResult<EarlyUnlockQuote> {
+    let config = CONFIG.load(deps.storage)?;
+    let user = deps.api.addr_validate(&addr)?;
+
+    let early_unlock = config.early_unlock
+        .clone()
+        .ok_or_else(|| StdError::generic_err(ContractError::EarlyUnlockDisabled {}.to_string()))?;
+
+    let claim = CLAIMABLE_AMOUNT.load(deps.storage, user.clone())?;
+    let instant = INSTANT_CLAIMABLE_AMOUNT.may_load(deps.storage, user)?.unwrap_or_default();
+
+    let quote = quote_early_unlock(&config, &early_unlock, instant, &claim, env.block.time, amount)
+        .map_err(|err| StdError::generic_err(err.to_string()))?;
+
+    Ok(EarlyUnlockQuote {
+        amount: quote.plan.applied,
+        penalty: quote.penalty,
+        released: quote.released,
+        destination: early_unlock.destination,
+    })
+}
+
+pub fn query_pending_rewards(deps: Deps, env: Env, addr: String) -> StdResult<Vec<Coin>> {
+    let user = Addr::unchecked(addr);
+    let config = CONFIG.load(deps.storage)?;
+    let state = STATE.load(deps.storage)?;
+
+    let claim = CLAIMABLE_AMOUNT.may_load(deps.storage, user.clone())?.unwrap_or_default();
+
+    pending_rewards(deps.storage, &state, &config, &user, &claim, env.block.time)
+}
+
+pub fn query_rewards(deps: Deps, env: Env) -> StdResult<RewardsResponse> {
+    let state = STATE.load(deps.storage)?;
+
+    Ok(RewardsResponse {
+        total_weight: total_locked(deps.storage, &state, env.block.time)?,
+        rewards: state.reward_totals,
+    })
+}
+
+pub fn query_merkle_allocation(deps: Deps) -> StdResult<MerkleAllocation> {
+    MERKLE_ALLOCATION.load(deps.storage)
+}
+
+pub fn query_is_registered(deps: Deps, addr: String) -> StdResult<bool> {
+    Ok(MERKLE_REGISTERED.has(deps.storage, Addr::unchecked(addr)))
+}
+
+pub fn query_grant(deps: Deps, grant_id: u64) -> StdResult<GrantResponse> {
+    let user = GRANTS.load(deps.storage, grant_id)?;
+
+    Ok(GrantResponse { grant_id, user })
+}
+
+pub fn query_claim_history(
+    deps: Deps,
+    addr: String,
+    start_after: Option<u64>,
+    limit: Option<u32>
+) -> StdResult<ClaimHistoryResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let claims = CLAIM_HISTORY.prefix(Addr::unchecked(addr))
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, record)| record))
+        .collect::<StdResult<Vec<ClaimRecord>>>()?;
+
+    Ok(ClaimHistoryResponse { claims })
+}
+
+pub fn query_pending_config_changes(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>
+) -> StdResult<PendingConfigChangesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let changes = PENDING_CONFIG_CHANGES.range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, change)| change))
+        .collect::<StdResult<Vec<PendingConfigChange>>>()?;
+
+    Ok(PendingConfigChangesResponse { changes })
+}
+
+pub fn query_proposal(deps: Deps, id: u64) -> StdResult<Proposal> {
+    PROPOSALS.load(deps.storage, id)
+}
+
+// Proposals that can still be approved or executed
+pub fn query_open_proposals(
+    deps: Deps,
+    env: Env,
+    start_after: Option<u64>,
+    limit: Option<u32>
+) -> StdResult<ProposalsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let proposals = PROPOSALS.range(deps.storage, start, None, Order::Ascending)
+        .filter(|item| {
+            item.as_ref().map_or(true, |(_, proposal)| {
+                !proposal.executed && env.block.time < proposal.expires
+            })
+        })
+        .take(limit)
+        .map(|item| item.map(|(_, proposal)| proposal))
+        .collect::<StdResult<Vec<Proposal>>>()?;
+
+    Ok(ProposalsResponse { proposals })
+}
+
+pub fn query_is_paused(deps: Deps) -> StdResult<bool> {
+    Ok(PAUSED.may_load(deps.storage)?.unwrap_or_default())
+}
+
+pub fn query_is_voucher_nonce_used(deps: Deps, nonce: u64) -> StdResult<bool> {
+    Ok(USED_VOUCHER_NONCES.has(deps.storage, nonce))
+}
+
+pub fn query_voting_power_at_height(
+    deps: Deps,
+    env: Env,
+    address: String,
+    height: Option<u64>
+) -> StdResult<VotingPowerAtHeightResponse> {
+    let height = height.unwrap_or(env.block.height);
+    let address = deps.api.addr_validate(&address)?;
+    let power = VOTING_POWER.may_load_at_height(deps.storage, address, height)?.unwrap_or_default();
+
+    Ok(VotingPowerAtHeightResponse { power, height })
+}
+
+pub fn query_total_power_at_height(
+    deps: Deps,
+    env: Env,
+    height: Option<u64>
+) -> StdResult<TotalPowerAtHeightResponse> {
+    let height = height.unwrap_or(env.block.height);
+    let power = TOTAL_POWER.may_load_at_height(deps.storage, height)?.unwrap_or_default();
+
+    Ok(TotalPowerAtHeightResponse { power, height })
+}
+
+pub fn query_info(deps: Deps) -> StdResult<InfoResponse> {
+    let info = get_contract_version(deps.storage)?;
+
+    Ok(InfoResponse { info })
+}
+
+// Creates or replaces the position of `user` and returns its `vest_created` event together
+// with the grant NFT mint when grants are tokenized
+fn create_vesting(
+    storage: &mut dyn Storage,
+    config: &Config,
+    user: &Addr,
+    amount: Uint256,
+    start: Timestamp,
+    block: &BlockInfo,
+    source: &str
+) -> Result<(Event, Option<CosmosMsg<InjectiveMsgWrapper>>), ContractError> {
+    let existing_grant = GRANT_IDS.may_load(storage, user.clone())?;
+
+    if config.grant_nft.is_some() && existing_grant.is_some() {
+        return Err(ContractError::GrantAlreadyExists {});
+    }
+
+    let (instant_claim_amount, amount_per_period) = split_vesting(
+        amount,
+        config.instant_claim_percents,
+        config.lock_periods
+    );
+
+    let mut state = STATE.load(storage)?;
+
+    let old_claim = CLAIMABLE_AMOUNT.may_load(storage, user.clone())?;
+
+    let new_claim = UserClaims {
+        amount: amount_per_period,
+        last_claimed: start,
+        amount_claimed: Uint256::zero(),
+        init_vesting: start,
+        instant_amount: instant_claim_amount,
+        base_period: 0,
+        base_vested: Uint256::zero(),
+    };
+
+    // bonus and rewards earned by a replaced position carry over to the new one
+    checkpoint_rewards(
+        storage,
+        &state,
+        config,
+        user,
+        &old_claim.clone().unwrap_or_default(),
+        block.time
+    )?;
+
+    // Vesting again replaces the existing position, so its share of the totals goes away
+    match old_claim {
+        Some(old_claim) => {
+            let old_instant = INSTANT_CLAIMABLE_AMOUNT.may_load(
+                storage,
+                user.clone()
+            )?.unwrap_or_default();
+
+            if
+                old_instant == Uint256::zero() &&
+                old_claim.amount_claimed == total_periodic(config, &old_claim)
+            {
+                state.fully_claimed_count -= 1;
+            }
+
+            // what the replaced position still owed is released back to the pool
+            state.total_vested -=
+                old_instant + total_periodic(config, &old_claim) - old_claim.amount_claimed;
+            state.instant_outstanding -= old_instant;
+            state.periodic_claimed -= old_claim.amount_claimed;
+
+            let (offset_plus, offset_minus) = periodic_offset(&old_claim);
+            state.periodic_offset_plus -= offset_plus;
+            state.periodic_offset_minus -= offset_minus;
+
+            update_cohort(
+                storage,
+                config,
+                &mut state,
+                old_claim.init_vesting.seconds(),
+                old_claim.amount,
+                Uint256::zero(),
+                block.time
+            )?;
+
+            let old_denom_claims = EXTRA_CLAIMS.prefix(user.clone())
+                .range(storage, None, None, Order::Ascending)
+                .collect::<StdResult<Vec<(String, DenomClaims)>>>()?;
+
+            for (denom, denom_claim) in old_denom_claims {
+                let schedule = denom_schedule(&old_claim, &denom_claim);
+
+                state.denom_totals(&denom).total_vested -=
+                    denom_claim.instant_claimable +
+                    total_periodic(config, &schedule) -
+                    denom_claim.amount_claimed;
+
+                EXTRA_CLAIMS.remove(storage, (user.clone(), denom));
+            }
+        }
+        None => {
+            state.beneficiary_count += 1;
+        }
+    }
+
+    state.instant_outstanding += instant_claim_amount;
+
+    update_cohort(
+        storage,
+        config,
+        &mut state,
+        start.seconds(),
+        Uint256::zero(),
+        amount_per_period,
+        block.time
+    )?;
+
+    STATE.save(storage, &state)?;
+
+    INSTANT_CLAIMABLE_AMOUNT.save(storage, user.clone(), &instant_claim_amount)?;
+
+    CLAIMABLE_AMOUNT.save(storage, user.clone(), &new_claim)?;
+
+    update_voting_power(storage, block.height, user)?;
+
+    let grant_id = match existing_grant {
+        Some(grant_id) => grant_id,
+        None => {
+            let grant_id = GRANT_COUNT.may_load(storage)?.unwrap_or_default() + 1;
+
+            GRANT_COUNT.save(storage, &grant_id)?;
+            GRANT_IDS.save(storage, user.clone(), &grant_id)?;
+            GRANTS.save(storage, grant_id, user)?;
+
+            grant_id
+        }
+    };
+
+    let mint_msg = match &config.grant_nft {
+        Some(nft_contract) =>
+            Some(
+                (WasmMsg::Execute {
+                    contract_addr: nft_contract.to_string(),
+                    msg: to_json_binary(
+                        &(cw721_base::ExecuteMsg::<Option<Empty>, Empty>::Mint {
+                            token_id: grant_id.to_string(),
+                            owner: user.to_string(),
+                            token_uri: None,
+                            extension: None,
+                        })
+                    )?,
+                    funds: vec![],
+                }).into()
+            ),
+        None => None,
+    };
+
+    let event = Event::new("vest_created")
+        .add_attribute("beneficiary", user.clone())
+        .add_attribute("grant_id", grant_id.to_string())
+        .add_attribute("source", source)
+        .add_attribute("amount", amount)
+        .add_attribute("instant_amount", instant_claim_amount)
+        .add_attribute("periodic_amount", amount_per_period * config.lock_periods)
+        .add_attribute("amount_per_period", amount_per_period)
+        .add_attribute("vesting_start", start.seconds().to_string())
+        .add_attribute("lock_minutes", config.lock_minutes)
+        .add_attribute("lock_periods", config.lock_periods);
+
+    Ok((event, mint_msg))
+}
+
+fn create_extra_vesting(
+    storage: &mut dyn Storage,
+    config: &Config,
+    user: &Addr,
+    extra: &[Coin]
+) -> StdResult<()> {
+    let mut state = STATE.load(storage)?;
+
+    for coin in extra {
+        let amount = Uint256::from_uint128(coin.amount);
+        let (instant_claim_amount, amount_per_period) = split_vesting(
+            amount,
+            config.instant_claim_percents,
+            config.lock_periods
+        );
+
+        EXTRA_CLAIMS.save(
+            storage,
+            (user.clone(), coin.denom.clone()),
+            &(DenomClaims {
+                amount: amount_per_period,
+                amount_claimed: Uint256::zero(),
+                instant_amount: instant_claim_amount,
+                instant_claimable: instant_claim_amount,
+            })
+        )?;
+
+        state.denom_totals(&coin.denom).total_vested += amount;
+    }
+
+    STATE.save(storage, &state)
+}
+
+// UserClaims of an extra denom position, so it runs through the same engine as the main one
+fn denom_schedule(claim: &UserClaims, denom_claim: &DenomClaims) -> UserClaims {
+    UserClaims {
+        amount: denom_claim.amount,
+        amount_claimed: denom_claim.amount_claimed,
+        instant_amount: denom_claim.instant_amount,
+        // amendments only apply to the main denom
+        base_period: 0,
+        base_vested: Uint256::zero(),
+        ..claim.clone()
+    }
+}
+
+fn coins_value(coins: &[Coin]) -> String {
+    coins
+        .iter()
+        .map(|coin| coin.to_string())
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+fn config_changed_event(
+    key: &str,
+    old_value: impl ToString,
+    new_value: impl ToString
+) -> Event {
+    Event::new("config_changed")
+        .add_attribute("key", key)
+        .add_attribute("old_value", old_value.to_string())
+        .add_attribute("new_value", new_value.to_string())
+}
+
+fn early_unlock_value(early_unlock: &Option<EarlyUnlockConfig>) -> StdResult<String> {
+    match early_unlock {
+        Some(early_unlock) => to_json_string(early_unlock),
+        None => Ok(String::new()),
+    }
+}
+
+fn penalty_destination_value(early_unlock: &EarlyUnlockConfig) -> StdResult<String> {
+    Ok(to_json_string(&early_unlock.destination)?.trim_matches('"').to_string())
+}
+
+fn validate_early_unlock(early_unlock: &EarlyUnlockConfig) -> Result<(), ContractError> {
+    if early_unlock.max_penalty_percents > PERCENTS {
+        return Err(ContractError::InvalidPercents {});
+    }
+
+    Ok(())
+}
+
+fn voucher_signer_value(signer: &Option<VoucherSigner>) -> StdResult<String> {
+    match signer {
+        Some(signer) => to_json_string(signer),
+        None => Ok(String::new()),
+    }
+}
+
+// Leaves are sha256("{address}:{amount}:{vesting_start_nanos}"), pairs are sorted before hashing
+fn verify_merkle_proof(
+    merkle_root: &str,
+    user: &Addr,
+    amount: Uint256,
+    vesting_start: Timestamp,
+    proof: Vec<String>
+) -> Result<(), ContractError> {
+    let leaf = format!("{}:{}:{}", user, amount, vesting_start.nanos());
+    let mut hash: [u8; 32] = sha2::Sha256::digest(leaf.as_bytes()).into();
+
+    for p in proof {
+        let mut proof_buf: [u8; 32] = [0; 32];
+        hex::decode_to_slice(p, &mut proof_buf).map_err(|_| ContractError::InvalidProof {})?;
+
+        let mut hashes = [hash, proof_buf];
+        hashes.sort_unstable();
+
+        hash = sha2::Sha256::digest(hashes.concat()).into();
+    }
+
+    let mut root_buf: [u8; 32] = [0; 32];
+    hex::decode_to_slice(merkle_root, &mut root_buf).map_err(
+        |_| ContractError::InvalidMerkleRoot {}
+    )?;
+
+    if root_buf != hash {
+        return Err(ContractError::InvalidProof {});
+    }
+
+    Ok(())
+}
+
+// Allocations claimed by the user themselves add a grant, they must not replace one that is
+// still vesting
+fn check_no_position(storage: &dyn Storage, user: &Addr) -> Result<(), ContractError> {
+    if CLAIMABLE_AMOUNT.has(storage, user.clone()) {
+        return Err(ContractError::PositionAlreadyExists {});
+    }
+
+    Ok(())
+}
+
+// Voting power is everything the user still has to receive: the unclaimed instant part
+// plus the periodic part that is either locked or unlocked but not yet claimed.
+// It is keyed by the position, i.e. the original beneficiary of a tokenized grant. cw721
+// transfers don't call back into this contract and the snapshots can't ask the NFT contract
+// for the holder at a past height, so the buyer of a grant NFT gets no voting power.
+fn update_voting_power(storage: &mut dyn Storage, height: u64, user: &Addr) -> StdResult<()> {
+    let config = CONFIG.load(storage)?;
+
+    // Every grant is tokenized once grant_nft is set and NFT transfers are not reported here,
+    // so nobody could be credited with the power reliably
+    if config.grant_nft.is_some() {
+        return Ok(());
+    }
+
+    let instant = INSTANT_CLAIMABLE_AMOUNT.may_load(storage, user.clone())?.unwrap_or_default();
+    let remaining = match CLAIMABLE_AMOUNT.may_load(storage, user.clone())? {
+        Some(claim) => total_periodic(&config, &claim) - claim.amount_claimed,
+        None => Uint256::zero(),
+    };
+
+    let power: Uint128 = (instant + remaining).try_into()?;
+    let old_power = VOTING_POWER.may_load(storage, user.clone())?.unwrap_or_default();
+
+    VOTING_POWER.save(storage, user.clone(), &power, height)?;
+    TOTAL_POWER.update(
+        storage,
+        height,
+        |total| -> StdResult<Uint128> { Ok(total.unwrap_or_default() - old_power + power) }
+    )?;
+
+    Ok(())
+}
+
+fn validate_voucher_signer(signer: &VoucherSigner) -> Result<(), ContractError> {
+    let is_valid = match signer {
+        VoucherSigner::Secp256k1 { public_key } => public_key.len() == 33 || public_key.len() == 65,
+        VoucherSigner::Ed25519 { public_key } => public_key.len() == 32,
+    };
+
+    if !is_valid {
+        return Err(ContractError::InvalidVoucherSigner {});
+    }
+
+    Ok(())
+}
+
+// Balance of the denom that is not owed to anyone, i.e. what can still be allocated
+fn unallocated_amount(deps: Deps, env: &Env, denom: &str) -> StdResult<Uint256> {
+    let config = CONFIG.load(deps.storage)?;
+    let state = STATE.load(deps.storage)?;
+
+    let balance = deps.querier.query_balance(env.contract.address.to_string(), denom)?;
+
+    let liabilities = if denom == config.injex_token {
+        let unregistered_merkle = match MERKLE_ALLOCATION.may_load(deps.storage)? {
+            Some(allocation) => allocation.total_amount - allocation.registered_amount,
+            None => Uint256::zero(),
+        };
+
+        state.total_vested - state.total_claimed + unregistered_merkle
+    } else {
+        state.extra_totals
+            .iter()
+            .find(|totals| totals.denom == denom)
+            .map(|totals| totals.total_vested - totals.total_claimed)
+            .unwrap_or_default()
+    } + state.rewards_outstanding(denom);
+
+    Ok(Uint256::from_uint128(balance.amount).saturating_sub(liabilities))
+}
+
+// Everything promised in the vesting denom, including merkle allocations not registered yet
+fn total_allocated(storage: &dyn Storage) -> StdResult<Uint256> {
+    let state = STATE.load(storage)?;
+
+    let unregistered_merkle = match MERKLE_ALLOCATION.may_load(storage)? {
+        Some(allocation) => allocation.total_amount - allocation.registered_amount,
+        None => Uint256::zero(),
+    };
+
+    Ok(state.total_vested + unregistered_merkle)
+}
+
+fn check_allocation_cap(
+    deps: Deps,
+    config: &Config,
+    amount: Uint256
+) -> Result<(), ContractError> {
+    if let Some(cap) = config.max_total_allocation {
+        if total_allocated(deps.storage)? + amount > cap {
+            return Err(ContractError::AllocationCapExceeded {});
+        }
+    }
+
+    if let Some(cap) = config.mint_cap {
+        if total_allocated(deps.storage)? + amount > cap {
+            return Err(ContractError::MintCapExceeded {});
+        }
+    }
+
+    Ok(())
+}
+
+// New allocations have to be backed by deposited tokens, unless they are minted on claim
+fn check_unallocated(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    amount: Uint256
+) -> Result<(), ContractError> {
+    if config.mint_cap.is_none() && unallocated_amount(deps, env, &config.injex_token)? < amount {
+        return Err(ContractError::InsufficientUnallocated {});
+    }
+
+    Ok(())
+}
+
+// Pays `coins` out to the recipient: injex_token is minted through the token-factory in mint
+// mode, everything else is sent from the contract balance
+fn payout_msgs(
+    storage: &mut dyn Storage,
+    env: &Env,
+    config: &Config,
+    recipient: &Addr,
+    coins: Vec<Coin>
+) -> Result<Vec<CosmosMsg<InjectiveMsgWrapper>>, ContractError> {
+    let mut msgs: Vec<CosmosMsg<InjectiveMsgWrapper>> = vec![];
+    let mut send_amount: Vec<Coin> = vec![];
+
+    for coin in coins {
+        match config.mint_cap {
+            Some(cap) if coin.denom == config.injex_token => {
+                let mut state = STATE.load(storage)?;
+                state.total_minted += Uint256::from_uint128(coin.amount);
+
+                if state.total_minted > cap {
+                    return Err(ContractError::MintCapExceeded {});
+                }
+
+                STATE.save(storage, &state)?;
+
+                msgs.push(
+                    create_mint_tokens_msg(
+                        env.contract.address.clone(),
+                        coin,
+                        recipient.to_string()
+                    )
+                );
+            }
+            _ => send_amount.push(coin),
+        }
+    }
+
+    if !send_amount.is_empty() {
+        msgs.push(
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: recipient.to_string(),
+                amount: send_amount,
+            })
+        );
+    }
+
+    Ok(msgs)
+}
+
+fn check_deposit_funds(config: &Config, funds: &[Coin]) -> Result<(), ContractError> {
+    let is_allowed_denom = |denom: &String| -> bool {
+        *denom == config.injex_token || config.extra_denoms.contains(denom)
+    };
+
+    if funds.iter().any(|coin| !is_allowed_denom(&coin.denom)) {
+        return Err(ContractError::InvalidCoin {});
+    }
+
+    Ok(())
+}
+
+fn validate_extra_denoms(injex_token: &str, denoms: &[String]) -> Result<(), ContractError> {
+    for (index, denom) in denoms.iter().enumerate() {
+        if denom.is_empty() || denom == injex_token || denoms[..index].contains(denom) {
+            return Err(ContractError::InvalidExtraDenoms {});
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_approvals(
+    api: &dyn Api,
+    approvals: ApprovalsMsg
+) -> Result<ApprovalConfig, ContractError> {
+    let approvers = approvals.approvers
+        .iter()
+        .map(|approver| api.addr_validate(approver))
+        .collect::<StdResult<Vec<Addr>>>()?;
+
+    let has_duplicates = approvers
+        .iter()
+        .enumerate()
+        .any(|(index, approver)| approvers[..index].contains(approver));
+
+    if
+        has_duplicates ||
+        approvals.threshold == 0 ||
+        (approvals.threshold as usize) > approvers.len() ||
+        approvals.proposal_ttl == 0
+    {
+        return Err(ContractError::InvalidApprovals {});
+    }
+
+    Ok(ApprovalConfig {
+        approvers,
+        threshold: approvals.threshold,
+        proposal_ttl: approvals.proposal_ttl,
+    })
+}
+
+fn approvals_value(approvals: &Option<ApprovalConfig>) -> StdResult<String> {
+    match approvals {
+        Some(approvals) => to_json_string(approvals),
+        None => Ok(String::new()),
+    }
+}
+
+fn validate_config_change(change: &ConfigChange) -> Result<(), ContractError> {
+    match change {
+        ConfigChange::LockMinutes { new_lock_minutes } => {
+            if *new_lock_minutes == Uint256::zero() {
+                return Err(ContractError::InvalidLockMinutes {});
+            }
+        }
+        ConfigChange::InstantClaimPercents { new_percents } => {
+            if *new_percents > PERCENTS {
+                return Err(ContractError::InvalidPercents {});
+            }
+        }
+        ConfigChange::MinDelay { new_min_delay } => {
+            if *new_min_delay == 0 {
+                return Err(ContractError::InvalidMinDelay {});
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn apply_config_change(
+    storage: &mut dyn Storage,
+    change: &ConfigChange,
+    time: Timestamp
+) -> Result<Event, ContractError> {
+    validate_config_change(change)?;
+
+    let mut config = CONFIG.load(storage)?;
+
+    let event = match change {
+        ConfigChange::LockMinutes { new_lock_minutes } => {
+            let old_lock_minutes = config.lock_minutes;
+            config.lock_minutes = *new_lock_minutes;
+
+            config_changed_event("lock_minutes", old_lock_minutes, new_lock_minutes)
+        }
+        ConfigChange::InstantClaimPercents { new_percents } => {
+            let old_percents = config.instant_claim_percents;
+            config.instant_claim_percents = *new_percents;
+
+            config_changed_event("instant_claim_percents", old_percents, new_percents)
+        }
+        ConfigChange::MinDelay { new_min_delay } => {
+            let old_min_delay = config.min_delay;
+            config.min_delay = *new_min_delay;
+
+            config_changed_event("min_delay", old_min_delay, new_min_delay)
+        }
+    };
+
+    CONFIG.save(storage, &config)?;
+
+    if let ConfigChange::LockMinutes { .. } = change {
+        reschedule_unlocks(storage, &config, time)?;
+    }
+
+    Ok(event)
+}
+
+fn validate_reward_denoms(denoms: &[String]) -> Result<(), ContractError> {
+    for (index, denom) in denoms.iter().enumerate() {
+        if denom.is_empty() || denoms[..index].contains(denom) {
+            return Err(ContractError::InvalidRewardDenoms {});
+        }
+    }
+
+    Ok(())
+}
+
+fn check_is_admin(deps: Deps, addr: Addr) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
     let admin = ADMIN.load(deps.storage)?;
     let is_admin = addr == admin;
 
@@ -22,4 +22,102 @@ pub enum ContractError {
     #[error("Insufficient contract balance")] InsufficientContractBalance {},
 
     #[error("Cannot claim")] CannotClaim {},
+
+    #[error("Invalid merkle root")] InvalidMerkleRoot {},
+
+    #[error("Merkle root is already set")] MerkleRootAlreadySet {},
+
+    #[error("Merkle root is not set")] MerkleRootNotSet {},
+
+    #[error("Invalid merkle proof")] InvalidProof {},
+
+    #[error("Already registered")] AlreadyRegistered {},
+
+    #[error("User already has a vesting position")] PositionAlreadyExists {},
+
+    #[error("Merkle allocation exceeded")] MerkleAllocationExceeded {},
+
+    #[error("Invalid voucher signer")] InvalidVoucherSigner {},
+
+    #[error("Voucher signer is not set")] VoucherSignerNotSet {},
+
+    #[error("Invalid voucher")] InvalidVoucher {},
+
+    #[error("Voucher expired")] VoucherExpired {},
+
+    #[error("Voucher nonce already used")] VoucherNonceUsed {},
+
+    #[error("Invalid voucher signature")] InvalidVoucherSignature {},
+
+    #[error("Grant NFT contract is not set")] GrantNftNotSet {},
+
+    #[error("Grants were already created")] GrantsAlreadyCreated {},
+
+    #[error("Grant already exists")] GrantAlreadyExists {},
+
+    #[error("Grant not found")] GrantNotFound {},
+
+    #[error("Grant is tokenized, claim it with the grant NFT")] GrantIsTokenized {},
+
+    #[error("Only grant NFT owner")] NotGrantOwner {},
+
+    #[error("Invalid extra denoms")] InvalidExtraDenoms {},
+
+    #[error("Insufficient unallocated funds")] InsufficientUnallocated {},
+
+    #[error("Max total allocation exceeded")] AllocationCapExceeded {},
+
+    #[error("Grant is fully vested")] GrantFullyVested {},
+
+    #[error("Decrease exceeds locked amount")] DecreaseExceedsLocked {},
+
+    #[error("Contract is paused")] Paused {},
+
+    #[error("Mint cap exceeded")] MintCapExceeded {},
+
+    #[error("Not supported in mint mode")] NotSupportedInMintMode {},
+
+    #[error("Early unlock is disabled")] EarlyUnlockDisabled {},
+
+    #[error("Treasury is not set")] TreasuryNotSet {},
+
+    #[error("Invalid reward denoms")] InvalidRewardDenoms {},
+
+    #[error("No positions to distribute rewards to")] NoRewardWeight {},
+
+    #[error("Change has to be scheduled")] TimelockRequired {},
+
+    #[error("Invalid min delay")] InvalidMinDelay {},
+
+    #[error("Effective time is earlier than the minimum delay")] EffectiveTimeTooEarly {},
+
+    #[error("Config change not found")] ConfigChangeNotFound {},
+
+    #[error("Config change is not effective yet")] ConfigChangeNotReady {},
+
+    #[error("Invalid approvals")] InvalidApprovals {},
+
+    #[error("Approvals are disabled")] ApprovalsDisabled {},
+
+    #[error("Action has to be approved through a proposal")] ApprovalRequired {},
+
+    #[error("Only privileged actions can be proposed")] NotPrivileged {},
+
+    #[error("Only approver")] NotApprover {},
+
+    #[error("Already approved")] AlreadyApproved {},
+
+    #[error("Proposal not found")] ProposalNotFound {},
+
+    #[error("Proposal expired")] ProposalExpired {},
+
+    #[error("Proposal was already executed")] ProposalAlreadyExecuted {},
+
+    #[error("Proposal has not reached the threshold")] ProposalNotApproved {},
+
+    #[error("Invalid label")] InvalidLabel {},
+
+    #[error("Unknown reply id")] UnknownReplyId {},
+
+    #[error("Invalid instantiate reply")] InvalidReply {},
 }
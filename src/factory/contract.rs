@@ -0,0 +1,339 @@
+#[cfg(feature = "factory")]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    to_json_binary,
+    Addr,
+    Binary,
+    Deps,
+    DepsMut,
+    Env,
+    Event,
+    MessageInfo,
+    Order,
+    Reply,
+    Response,
+    StdResult,
+    SubMsg,
+    WasmMsg,
+};
+use cw2::set_contract_version;
+use cw_storage_plus::Bound;
+use cw_utils::parse_reply_instantiate_data;
+
+use crate::error::ContractError;
+use crate::factory::msg::{
+    DenomPositionTotals,
+    ExecuteMsg,
+    FactoryConfigResponse,
+    InstancePosition,
+    InstancesResponse,
+    InstantiateMsg,
+    QueryMsg,
+    UserPositionsResponse,
+};
+use crate::factory::state::{
+    FactoryConfig,
+    Instance,
+    PendingInstance,
+    FACTORY_CONFIG,
+    INSTANCES,
+    INSTANCE_COUNT,
+    INSTANCE_IDS,
+    PENDING_INSTANCE,
+};
+use crate::helpers::VestingContract;
+use crate::msg::InstantiateMsg as VestingInstantiateMsg;
+
+// version info for migration info
+const CONTRACT_NAME: &str = "injex-vesting-factory";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+// pagination
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+pub const INSTANTIATE_REPLY_ID: u64 = 1;
+
+#[cfg_attr(feature = "factory", entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg
+) -> Result<Response, ContractError> {
+    let config = FactoryConfig {
+        admin: deps.api.addr_validate(&msg.admin)?,
+        vesting_code_id: msg.vesting_code_id,
+    };
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    FACTORY_CONFIG.save(deps.storage, &config)?;
+    INSTANCE_COUNT.save(deps.storage, &0)?;
+
+    Ok(
+        Response::new()
+            .add_attribute("method", "instantiate")
+            .add_attribute("owner", info.sender)
+            .add_attribute("admin", config.admin)
+            .add_attribute("vesting_code_id", config.vesting_code_id.to_string())
+    )
+}
+
+#[cfg_attr(feature = "factory", entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::CreateInstance { label, msg } => create_instance(deps, info, label, *msg),
+        ExecuteMsg::ChangeVestingCodeId { new_code_id } =>
+            change_vesting_code_id(deps, info, new_code_id),
+        ExecuteMsg::ChangeAdmin { new_admin } => change_admin(deps, info, new_admin),
+    }
+}
+
+pub fn create_instance(
+    deps: DepsMut,
+    info: MessageInfo,
+    label: String,
+    msg: VestingInstantiateMsg
+) -> Result<Response, ContractError> {
+    let config = check_is_admin(deps.as_ref(), info.sender)?;
+
+    if label.is_empty() {
+        return Err(ContractError::InvalidLabel {});
+    }
+
+    let owner = deps.api.addr_validate(&msg.admin)?;
+
+    PENDING_INSTANCE.save(
+        deps.storage,
+        &(PendingInstance {
+            label: label.clone(),
+            owner,
+            code_id: config.vesting_code_id,
+        })
+    )?;
+
+    let instantiate_msg = WasmMsg::Instantiate {
+        // the factory admin can migrate every instance it created
+        admin: Some(config.admin.to_string()),
+        code_id: config.vesting_code_id,
+        msg: to_json_binary(&msg)?,
+        funds: info.funds,
+        label: label.clone(),
+    };
+
+    Ok(
+        Response::new()
+            .add_submessage(SubMsg::reply_on_success(instantiate_msg, INSTANTIATE_REPLY_ID))
+            .add_attribute("method", "create_instance")
+            .add_attribute("label", label)
+    )
+}
+
+pub fn change_vesting_code_id(
+    deps: DepsMut,
+    info: MessageInfo,
+    new_code_id: u64
+) -> Result<Response, ContractError> {
+    let mut config = check_is_admin(deps.as_ref(), info.sender)?;
+    let old_code_id = config.vesting_code_id;
+
+    config.vesting_code_id = new_code_id;
+
+    FACTORY_CONFIG.save(deps.storage, &config)?;
+
+    Ok(
+        Response::new()
+            .add_event(
+                Event::new("config_changed")
+                    .add_attribute("key", "vesting_code_id")
+                    .add_attribute("old_value", old_code_id.to_string())
+                    .add_attribute("new_value", new_code_id.to_string())
+            )
+            .add_attribute("method", "change_vesting_code_id")
+    )
+}
+
+pub fn change_admin(
+    deps: DepsMut,
+    info: MessageInfo,
+    new_admin: String
+) -> Result<Response, ContractError> {
+    let mut config = check_is_admin(deps.as_ref(), info.sender)?;
+    let old_admin = config.admin;
+
+    config.admin = deps.api.addr_validate(&new_admin)?;
+
+    FACTORY_CONFIG.save(deps.storage, &config)?;
+
+    Ok(
+        Response::new()
+            .add_event(
+                Event::new("admin_changed")
+                    .add_attribute("old_admin", old_admin)
+                    .add_attribute("new_admin", config.admin)
+            )
+            .add_attribute("method", "change_admin")
+    )
+}
+
+#[cfg_attr(feature = "factory", entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    if msg.id != INSTANTIATE_REPLY_ID {
+        return Err(ContractError::UnknownReplyId {});
+    }
+
+    let res = parse_reply_instantiate_data(msg).map_err(|_| ContractError::InvalidReply {})?;
+    let address = deps.api.addr_validate(&res.contract_address)?;
+
+    let pending = PENDING_INSTANCE.load(deps.storage)?;
+    PENDING_INSTANCE.remove(deps.storage);
+
+    let id = INSTANCE_COUNT.load(deps.storage)? + 1;
+
+    let instance = Instance {
+        id,
+        address: address.clone(),
+        label: pending.label,
+        owner: pending.owner,
+        code_id: pending.code_id,
+        created_at: env.block.time,
+    };
+
+    INSTANCE_COUNT.save(deps.storage, &id)?;
+    INSTANCES.save(deps.storage, id, &instance)?;
+    INSTANCE_IDS.save(deps.storage, address.clone(), &id)?;
+
+    Ok(
+        Response::new()
+            .add_event(
+                Event::new("instance_created")
+                    .add_attribute("instance_id", id.to_string())
+                    .add_attribute("address", address)
+                    .add_attribute("label", instance.label)
+                    .add_attribute("owner", instance.owner)
+                    .add_attribute("code_id", instance.code_id.to_string())
+            )
+            .add_attribute("method", "reply_instantiate")
+    )
+}
+
+#[cfg_attr(feature = "factory", entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::GetConfig {} => to_json_binary(&query_config(deps)?),
+        QueryMsg::GetInstance { id } => to_json_binary(&INSTANCES.load(deps.storage, id)?),
+        QueryMsg::GetInstanceByAddress { address } =>
+            to_json_binary(&query_instance_by_address(deps, address)?),
+        QueryMsg::Instances { start_after, limit } =>
+            to_json_binary(&query_instances(deps, start_after, limit)?),
+        QueryMsg::UserPositions { addr, start_after, limit } =>
+            to_json_binary(&query_user_positions(deps, _env, addr, start_after, limit)?),
+    }
+}
+
+pub fn query_config(deps: Deps) -> StdResult<FactoryConfigResponse> {
+    let config = FACTORY_CONFIG.load(deps.storage)?;
+
+    Ok(FactoryConfigResponse {
+        admin: config.admin,
+        vesting_code_id: config.vesting_code_id,
+        instance_count: INSTANCE_COUNT.load(deps.storage)?,
+    })
+}
+
+pub fn query_instance_by_address(deps: Deps, address: String) -> StdResult<Instance> {
+    let address = deps.api.addr_validate(&address)?;
+    let id = INSTANCE_IDS.load(deps.storage, address)?;
+
+    INSTANCES.load(deps.storage, id)
+}
+
+pub fn query_instances(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>
+) -> StdResult<InstancesResponse> {
+    Ok(InstancesResponse { instances: load_instances(deps, start_after, limit)? })
+}
+
+// Positions are read from every instance on the page, so the page size bounds the gas used
+pub fn query_user_positions(
+    deps: Deps,
+    env: Env,
+    addr: String,
+    start_after: Option<u64>,
+    limit: Option<u32>
+) -> StdResult<UserPositionsResponse> {
+    let mut positions: Vec<InstancePosition> = vec![];
+    let mut totals: Vec<DenomPositionTotals> = vec![];
+
+    for instance in load_instances(deps, start_after, limit)? {
+        let vesting = VestingContract(instance.address.clone());
+
+        let position = vesting.simulate_at(&deps.querier, addr.clone(), env.block.time)?;
+
+        if position.vested.is_zero() && position.locked.is_zero() {
+            continue;
+        }
+
+        let denom = vesting.injx_token(&deps.querier)?.denom;
+
+        match totals.iter_mut().find(|totals| totals.denom == denom) {
+            Some(denom_totals) => {
+                denom_totals.claimable += position.claimable;
+                denom_totals.vested += position.vested;
+                denom_totals.locked += position.locked;
+            }
+            None =>
+                totals.push(DenomPositionTotals {
+                    denom: denom.clone(),
+                    claimable: position.claimable,
+                    vested: position.vested,
+                    locked: position.locked,
+                }),
+        }
+
+        positions.push(InstancePosition {
+            instance_id: instance.id,
+            address: instance.address,
+            label: instance.label,
+            denom,
+            claimable: position.claimable,
+            vested: position.vested,
+            locked: position.locked,
+            next_unlock: position.next_unlock,
+        });
+    }
+
+    Ok(UserPositionsResponse { positions, totals })
+}
+
+fn load_instances(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>
+) -> StdResult<Vec<Instance>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    INSTANCES.range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, instance)| instance))
+        .collect()
+}
+
+fn check_is_admin(deps: Deps, addr: Addr) -> Result<FactoryConfig, ContractError> {
+    let config = FACTORY_CONFIG.load(deps.storage)?;
+
+    if config.admin != addr {
+        return Err(ContractError::OnlyAdmin {});
+    }
+
+    Ok(config)
+}
@@ -0,0 +1,94 @@
+use cosmwasm_std::{ to_json_binary, Addr, Coin, CosmosMsg, QuerierWrapper, StdResult, WasmMsg };
+
+use crate::factory::msg::{
+    ExecuteMsg,
+    FactoryConfigResponse,
+    InstancesResponse,
+    QueryMsg,
+    UserPositionsResponse,
+};
+use crate::factory::state::Instance;
+use crate::msg::InstantiateMsg as VestingInstantiateMsg;
+
+/// FactoryContract is a wrapper around Addr that provides typed helpers
+/// for building messages to and querying the vesting factory.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FactoryContract(pub Addr);
+
+impl FactoryContract {
+    pub fn addr(&self) -> Addr {
+        self.0.clone()
+    }
+
+    pub fn call_with_funds<C>(&self, msg: ExecuteMsg, funds: Vec<Coin>) -> StdResult<CosmosMsg<C>> {
+        Ok(
+            (WasmMsg::Execute {
+                contract_addr: self.addr().into(),
+                msg: to_json_binary(&msg)?,
+                funds,
+            }).into()
+        )
+    }
+
+    /// `funds` are forwarded to the new instance
+    pub fn create_instance<C>(
+        &self,
+        label: impl Into<String>,
+        msg: VestingInstantiateMsg,
+        funds: Vec<Coin>
+    ) -> StdResult<CosmosMsg<C>> {
+        self.call_with_funds(
+            ExecuteMsg::CreateInstance { label: label.into(), msg: Box::new(msg) },
+            funds
+        )
+    }
+
+    pub fn change_vesting_code_id<C>(&self, new_code_id: u64) -> StdResult<CosmosMsg<C>> {
+        self.call_with_funds(ExecuteMsg::ChangeVestingCodeId { new_code_id }, vec![])
+    }
+
+    pub fn change_admin<C>(&self, new_admin: impl Into<String>) -> StdResult<CosmosMsg<C>> {
+        self.call_with_funds(ExecuteMsg::ChangeAdmin { new_admin: new_admin.into() }, vec![])
+    }
+
+    pub fn config(&self, querier: &QuerierWrapper) -> StdResult<FactoryConfigResponse> {
+        querier.query_wasm_smart(self.addr(), &(QueryMsg::GetConfig {}))
+    }
+
+    pub fn instance(&self, querier: &QuerierWrapper, id: u64) -> StdResult<Instance> {
+        querier.query_wasm_smart(self.addr(), &(QueryMsg::GetInstance { id }))
+    }
+
+    pub fn instance_by_address(
+        &self,
+        querier: &QuerierWrapper,
+        address: impl Into<String>
+    ) -> StdResult<Instance> {
+        querier.query_wasm_smart(
+            self.addr(),
+            &(QueryMsg::GetInstanceByAddress { address: address.into() })
+        )
+    }
+
+    pub fn instances(
+        &self,
+        querier: &QuerierWrapper,
+        start_after: Option<u64>,
+        limit: Option<u32>
+    ) -> StdResult<InstancesResponse> {
+        querier.query_wasm_smart(self.addr(), &(QueryMsg::Instances { start_after, limit }))
+    }
+
+    pub fn user_positions(
+        &self,
+        querier: &QuerierWrapper,
+        addr: impl Into<String>,
+        start_after: Option<u64>,
+        limit: Option<u32>
+    ) -> StdResult<UserPositionsResponse> {
+        querier.query_wasm_smart(
+            self.addr(),
+            &(QueryMsg::UserPositions { addr: addr.into(), start_after, limit })
+        )
+    }
+}
@@ -0,0 +1,6 @@
+//! Factory instantiating vesting contracts (one per sale round) and keeping a registry of them.
+//! Build with the `factory` feature to export its entry points instead of the vesting ones.
+pub mod contract;
+pub mod helpers;
+pub mod msg;
+pub mod state;
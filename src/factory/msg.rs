@@ -0,0 +1,85 @@
+use cosmwasm_std::{ Addr, Timestamp, Uint256 };
+use cosmwasm_schema::{ cw_serde, QueryResponses };
+
+use crate::factory::state::Instance;
+use crate::msg::InstantiateMsg as VestingInstantiateMsg;
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    pub admin: String,
+    pub vesting_code_id: u64,
+}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    CreateInstance {
+        label: String,
+        msg: Box<VestingInstantiateMsg>,
+    },
+    ChangeVestingCodeId {
+        new_code_id: u64,
+    },
+    ChangeAdmin {
+        new_admin: String,
+    },
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    #[returns(FactoryConfigResponse)] GetConfig {},
+    #[returns(Instance)] GetInstance {
+        id: u64,
+    },
+    #[returns(Instance)] GetInstanceByAddress {
+        address: String,
+    },
+    #[returns(InstancesResponse)] Instances {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    #[returns(UserPositionsResponse)] UserPositions {
+        addr: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+}
+
+#[cw_serde]
+pub struct FactoryConfigResponse {
+    pub admin: Addr,
+    pub vesting_code_id: u64,
+    pub instance_count: u64,
+}
+
+#[cw_serde]
+pub struct InstancesResponse {
+    pub instances: Vec<Instance>,
+}
+
+#[cw_serde]
+pub struct InstancePosition {
+    pub instance_id: u64,
+    pub address: Addr,
+    pub label: String,
+    pub denom: String,
+    pub claimable: Uint256,
+    pub vested: Uint256,
+    pub locked: Uint256,
+    pub next_unlock: Option<Timestamp>,
+}
+
+#[cw_serde]
+pub struct DenomPositionTotals {
+    pub denom: String,
+    pub claimable: Uint256,
+    pub vested: Uint256,
+    pub locked: Uint256,
+}
+
+#[cw_serde]
+pub struct UserPositionsResponse {
+    pub positions: Vec<InstancePosition>,
+    // sums of the returned positions per vesting denom
+    pub totals: Vec<DenomPositionTotals>,
+}
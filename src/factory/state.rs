@@ -0,0 +1,40 @@
+use schemars::JsonSchema;
+use serde::{ Deserialize, Serialize };
+
+use cosmwasm_std::{ Addr, Timestamp };
+use cw_storage_plus::{ Item, Map };
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FactoryConfig {
+    pub admin: Addr,
+    pub vesting_code_id: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Instance {
+    pub id: u64,
+    pub address: Addr,
+    pub label: String,
+    // admin of the vesting instance
+    pub owner: Addr,
+    pub code_id: u64,
+    pub created_at: Timestamp,
+}
+
+// Instance waiting for its address from the instantiate reply
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingInstance {
+    pub label: String,
+    pub owner: Addr,
+    pub code_id: u64,
+}
+
+pub const FACTORY_CONFIG: Item<FactoryConfig> = Item::new("factory_config");
+
+pub const INSTANCE_COUNT: Item<u64> = Item::new("instance_count");
+
+pub const INSTANCES: Map<u64, Instance> = Map::new("instances");
+
+pub const INSTANCE_IDS: Map<Addr, u64> = Map::new("instance_ids");
+
+pub const PENDING_INSTANCE: Item<PendingInstance> = Item::new("pending_instance");
@@ -1,28 +1,448 @@
 use schemars::JsonSchema;
-use serde::{ Deserialize, Serialize };
+use serde::{ de::DeserializeOwned, Deserialize, Serialize };
 
-use cosmwasm_std::{ to_json_binary, Addr, CosmosMsg, StdResult, WasmMsg };
+use cosmwasm_std::{
+    to_json_binary,
+    Addr,
+    Binary,
+    Coin,
+    CosmosMsg,
+    QuerierWrapper,
+    StdResult,
+    Timestamp,
+    Uint256,
+    WasmMsg,
+};
 
-use crate::msg::ExecuteMsg;
+use crate::msg::{
+    ApprovalsMsg,
+    AuditPositionsResponse,
+    ClaimHistoryResponse,
+    ConfigResponse,
+    DenomResponse,
+    EarlyUnlockQuote,
+    ExecuteMsg,
+    GrantResponse,
+    InfoResponse,
+    PendingConfigChangesResponse,
+    ProposalsResponse,
+    QueryMsg,
+    RewardsResponse,
+    SimulateAtResponse,
+    SolvencyResponse,
+    StatsResponse,
+    TotalPowerAtHeightResponse,
+    TotalsResponse,
+    UnallocatedResponse,
+    UnlockScheduleResponse,
+    UserVesting,
+    UserVestingInfo,
+    Voucher,
+    VotingPowerAtHeightResponse,
+};
+use crate::state::{
+    ConfigChange,
+    EarlyUnlockConfig,
+    MerkleAllocation,
+    Proposal,
+    VoucherSigner,
+};
 
-/// CwTemplateContract is a wrapper around Addr that provides a lot of helpers
-/// for working with this.
+/// VestingContract is a wrapper around Addr that provides typed helpers
+/// for building messages to and querying the vesting contract.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct CwTemplateContract(pub Addr);
+pub struct VestingContract(pub Addr);
 
-impl CwTemplateContract {
+impl VestingContract {
     pub fn addr(&self) -> Addr {
         self.0.clone()
     }
 
-    pub fn call<T: Into<ExecuteMsg>>(&self, msg: T) -> StdResult<CosmosMsg> {
+    pub fn call<T: Into<ExecuteMsg>, C>(&self, msg: T) -> StdResult<CosmosMsg<C>> {
+        self.call_with_funds(msg, vec![])
+    }
+
+    pub fn call_with_funds<T: Into<ExecuteMsg>, C>(
+        &self,
+        msg: T,
+        funds: Vec<Coin>
+    ) -> StdResult<CosmosMsg<C>> {
         let msg = to_json_binary(&msg.into())?;
         Ok(
             (WasmMsg::Execute {
                 contract_addr: self.addr().into(),
                 msg,
-                funds: vec![],
+                funds,
             }).into()
         )
     }
+
+    pub fn claim<C>(&self) -> StdResult<CosmosMsg<C>> {
+        self.call(ExecuteMsg::Claim {})
+    }
+
+    pub fn claim_grant<C>(&self, grant_id: u64) -> StdResult<CosmosMsg<C>> {
+        self.call(ExecuteMsg::ClaimGrant { grant_id })
+    }
+
+    /// Allocates from the unallocated pool, attached `funds` are deposited into it first
+    pub fn vest_tokens<C>(
+        &self,
+        users: Vec<UserVesting>,
+        funds: Vec<Coin>
+    ) -> StdResult<CosmosMsg<C>> {
+        self.call_with_funds(ExecuteMsg::VestTokens { users }, funds)
+    }
+
+    pub fn change_admin<C>(&self, new_admin: impl Into<String>) -> StdResult<CosmosMsg<C>> {
+        self.call(ExecuteMsg::ChangeAdmin { new_admin: new_admin.into() })
+    }
+
+    pub fn change_instant_claim_percents<C>(
+        &self,
+        new_percents: Uint256
+    ) -> StdResult<CosmosMsg<C>> {
+        self.call(ExecuteMsg::ChangeInstantClaimPercents { new_percents })
+    }
+
+    pub fn change_lock_minutes<C>(&self, new_lock_minutes: Uint256) -> StdResult<CosmosMsg<C>> {
+        self.call(ExecuteMsg::ChangeLockMinutes { new_lock_minutes })
+    }
+
+    pub fn register_merkle_root<C>(
+        &self,
+        merkle_root: impl Into<String>,
+        total_amount: Uint256,
+        funds: Vec<Coin>
+    ) -> StdResult<CosmosMsg<C>> {
+        self.call_with_funds(
+            ExecuteMsg::RegisterMerkleRoot { merkle_root: merkle_root.into(), total_amount },
+            funds
+        )
+    }
+
+    pub fn register_vesting<C>(
+        &self,
+        amount: Uint256,
+        vesting_start: Timestamp,
+        proof: Vec<String>
+    ) -> StdResult<CosmosMsg<C>> {
+        self.call(ExecuteMsg::RegisterVesting { amount, vesting_start, proof })
+    }
+
+    pub fn change_voucher_signer<C>(
+        &self,
+        new_signer: Option<VoucherSigner>
+    ) -> StdResult<CosmosMsg<C>> {
+        self.call(ExecuteMsg::ChangeVoucherSigner { new_signer })
+    }
+
+    pub fn redeem_voucher<C>(
+        &self,
+        voucher: Voucher,
+        signature: Binary
+    ) -> StdResult<CosmosMsg<C>> {
+        self.call(ExecuteMsg::RedeemVoucher { voucher, signature })
+    }
+
+    pub fn set_grant_nft<C>(&self, nft_contract: impl Into<String>) -> StdResult<CosmosMsg<C>> {
+        self.call(ExecuteMsg::SetGrantNft { nft_contract: nft_contract.into() })
+    }
+
+    /// Deposits `funds` into the unallocated pool that `vest_tokens` allocates from
+    pub fn deposit<C>(&self, funds: Vec<Coin>) -> StdResult<CosmosMsg<C>> {
+        self.call_with_funds(ExecuteMsg::Deposit {}, funds)
+    }
+
+    pub fn set_max_total_allocation<C>(
+        &self,
+        max_total_allocation: Option<Uint256>
+    ) -> StdResult<CosmosMsg<C>> {
+        self.call(ExecuteMsg::SetMaxTotalAllocation { max_total_allocation })
+    }
+
+    pub fn change_treasury<C>(&self, new_treasury: Option<String>) -> StdResult<CosmosMsg<C>> {
+        self.call(ExecuteMsg::ChangeTreasury { new_treasury })
+    }
+
+    pub fn increase_grant<C>(
+        &self,
+        user: impl Into<String>,
+        grant_id: u64,
+        amount: Uint256,
+        funds: Vec<Coin>
+    ) -> StdResult<CosmosMsg<C>> {
+        self.call_with_funds(
+            ExecuteMsg::IncreaseGrant { user: user.into(), grant_id, amount },
+            funds
+        )
+    }
+
+    pub fn decrease_grant<C>(
+        &self,
+        user: impl Into<String>,
+        grant_id: u64,
+        amount: Uint256
+    ) -> StdResult<CosmosMsg<C>> {
+        self.call(ExecuteMsg::DecreaseGrant { user: user.into(), grant_id, amount })
+    }
+
+    pub fn set_extra_denoms<C>(&self, denoms: Vec<String>) -> StdResult<CosmosMsg<C>> {
+        self.call(ExecuteMsg::SetExtraDenoms { denoms })
+    }
+
+    pub fn set_early_unlock<C>(
+        &self,
+        early_unlock: Option<EarlyUnlockConfig>
+    ) -> StdResult<CosmosMsg<C>> {
+        self.call(ExecuteMsg::SetEarlyUnlock { early_unlock })
+    }
+
+    pub fn early_unlock<C>(&self, amount: Uint256) -> StdResult<CosmosMsg<C>> {
+        self.call(ExecuteMsg::EarlyUnlock { amount })
+    }
+
+    pub fn set_reward_denoms<C>(&self, denoms: Vec<String>) -> StdResult<CosmosMsg<C>> {
+        self.call(ExecuteMsg::SetRewardDenoms { denoms })
+    }
+
+    pub fn distribute_rewards<C>(&self, funds: Vec<Coin>) -> StdResult<CosmosMsg<C>> {
+        self.call_with_funds(ExecuteMsg::DistributeRewards {}, funds)
+    }
+
+    pub fn claim_rewards<C>(&self, grant_id: Option<u64>) -> StdResult<CosmosMsg<C>> {
+        self.call(ExecuteMsg::ClaimRewards { grant_id })
+    }
+
+    pub fn schedule_config_change<C>(
+        &self,
+        change: ConfigChange,
+        effective_time: Timestamp
+    ) -> StdResult<CosmosMsg<C>> {
+        self.call(ExecuteMsg::ScheduleConfigChange { change, effective_time })
+    }
+
+    pub fn cancel_config_change<C>(&self, id: u64) -> StdResult<CosmosMsg<C>> {
+        self.call(ExecuteMsg::CancelConfigChange { id })
+    }
+
+    pub fn execute_config_change<C>(&self, id: u64) -> StdResult<CosmosMsg<C>> {
+        self.call(ExecuteMsg::ExecuteConfigChange { id })
+    }
+
+    pub fn set_approvals<C>(&self, approvals: Option<ApprovalsMsg>) -> StdResult<CosmosMsg<C>> {
+        self.call(ExecuteMsg::SetApprovals { approvals })
+    }
+
+    pub fn propose<C>(&self, msg: ExecuteMsg) -> StdResult<CosmosMsg<C>> {
+        self.call(ExecuteMsg::Propose { msg: Box::new(msg) })
+    }
+
+    pub fn approve<C>(&self, id: u64) -> StdResult<CosmosMsg<C>> {
+        self.call(ExecuteMsg::Approve { id })
+    }
+
+    pub fn execute_proposal<C>(&self, id: u64) -> StdResult<CosmosMsg<C>> {
+        self.call(ExecuteMsg::ExecuteProposal { id })
+    }
+
+    pub fn query<T: DeserializeOwned>(
+        &self,
+        querier: &QuerierWrapper,
+        msg: &QueryMsg
+    ) -> StdResult<T> {
+        querier.query_wasm_smart(self.addr(), msg)
+    }
+
+    pub fn claimable_amount(
+        &self,
+        querier: &QuerierWrapper,
+        addr: impl Into<String>
+    ) -> StdResult<Uint256> {
+        self.query(querier, &(QueryMsg::GetClaimableAmount { addr: addr.into() }))
+    }
+
+    pub fn instant_claim(
+        &self,
+        querier: &QuerierWrapper,
+        addr: impl Into<String>
+    ) -> StdResult<Uint256> {
+        self.query(querier, &(QueryMsg::GetInstantClaim { addr: addr.into() }))
+    }
+
+    pub fn extra_claimable(
+        &self,
+        querier: &QuerierWrapper,
+        addr: impl Into<String>
+    ) -> StdResult<Vec<Coin>> {
+        self.query(querier, &(QueryMsg::GetExtraClaimable { addr: addr.into() }))
+    }
+
+    pub fn vested_amount(
+        &self,
+        querier: &QuerierWrapper,
+        addr: impl Into<String>
+    ) -> StdResult<UserVestingInfo> {
+        self.query(querier, &(QueryMsg::GetVestedAmount { addr: addr.into() }))
+    }
+
+    pub fn injx_token(&self, querier: &QuerierWrapper) -> StdResult<DenomResponse> {
+        self.query(querier, &(QueryMsg::GetInjxToken {}))
+    }
+
+    pub fn config(&self, querier: &QuerierWrapper) -> StdResult<ConfigResponse> {
+        self.query(querier, &(QueryMsg::GetConfig {}))
+    }
+
+    pub fn total_claimed(&self, querier: &QuerierWrapper) -> StdResult<Uint256> {
+        self.query(querier, &(QueryMsg::GetTotalClaimed {}))
+    }
+
+    pub fn total_vested(&self, querier: &QuerierWrapper) -> StdResult<Uint256> {
+        self.query(querier, &(QueryMsg::GetTotalVested {}))
+    }
+
+    pub fn totals(&self, querier: &QuerierWrapper) -> StdResult<TotalsResponse> {
+        self.query(querier, &(QueryMsg::GetTotals {}))
+    }
+
+    pub fn unallocated(&self, querier: &QuerierWrapper) -> StdResult<UnallocatedResponse> {
+        self.query(querier, &(QueryMsg::GetUnallocated {}))
+    }
+
+    pub fn is_paused(&self, querier: &QuerierWrapper) -> StdResult<bool> {
+        self.query(querier, &(QueryMsg::IsPaused {}))
+    }
+
+    pub fn stats(&self, querier: &QuerierWrapper) -> StdResult<StatsResponse> {
+        self.query(querier, &(QueryMsg::Stats {}))
+    }
+
+    pub fn check_solvency(&self, querier: &QuerierWrapper) -> StdResult<SolvencyResponse> {
+        self.query(querier, &(QueryMsg::CheckSolvency {}))
+    }
+
+    pub fn audit_positions(
+        &self,
+        querier: &QuerierWrapper,
+        start_after: Option<String>,
+        limit: Option<u32>
+    ) -> StdResult<AuditPositionsResponse> {
+        self.query(querier, &(QueryMsg::AuditPositions { start_after, limit }))
+    }
+
+    pub fn voting_power_at_height(
+        &self,
+        querier: &QuerierWrapper,
+        address: impl Into<String>,
+        height: Option<u64>
+    ) -> StdResult<VotingPowerAtHeightResponse> {
+        self.query(querier, &(QueryMsg::VotingPowerAtHeight { address: address.into(), height }))
+    }
+
+    pub fn total_power_at_height(
+        &self,
+        querier: &QuerierWrapper,
+        height: Option<u64>
+    ) -> StdResult<TotalPowerAtHeightResponse> {
+        self.query(querier, &(QueryMsg::TotalPowerAtHeight { height }))
+    }
+
+    pub fn info(&self, querier: &QuerierWrapper) -> StdResult<InfoResponse> {
+        self.query(querier, &(QueryMsg::Info {}))
+    }
+
+    pub fn dao(&self, querier: &QuerierWrapper) -> StdResult<Addr> {
+        self.query(querier, &(QueryMsg::Dao {}))
+    }
+
+    pub fn merkle_allocation(&self, querier: &QuerierWrapper) -> StdResult<MerkleAllocation> {
+        self.query(querier, &(QueryMsg::GetMerkleAllocation {}))
+    }
+
+    pub fn is_registered(
+        &self,
+        querier: &QuerierWrapper,
+        addr: impl Into<String>
+    ) -> StdResult<bool> {
+        self.query(querier, &(QueryMsg::IsRegistered { addr: addr.into() }))
+    }
+
+    pub fn is_voucher_nonce_used(&self, querier: &QuerierWrapper, nonce: u64) -> StdResult<bool> {
+        self.query(querier, &(QueryMsg::IsVoucherNonceUsed { nonce }))
+    }
+
+    pub fn grant(&self, querier: &QuerierWrapper, grant_id: u64) -> StdResult<GrantResponse> {
+        self.query(querier, &(QueryMsg::GetGrant { grant_id }))
+    }
+
+    pub fn simulate_at(
+        &self,
+        querier: &QuerierWrapper,
+        addr: impl Into<String>,
+        timestamp: Timestamp
+    ) -> StdResult<SimulateAtResponse> {
+        self.query(querier, &(QueryMsg::SimulateAt { addr: addr.into(), timestamp }))
+    }
+
+    pub fn unlock_schedule(
+        &self,
+        querier: &QuerierWrapper,
+        addr: impl Into<String>
+    ) -> StdResult<UnlockScheduleResponse> {
+        self.query(querier, &(QueryMsg::UnlockSchedule { addr: addr.into() }))
+    }
+
+    pub fn claim_history(
+        &self,
+        querier: &QuerierWrapper,
+        addr: impl Into<String>,
+        start_after: Option<u64>,
+        limit: Option<u32>
+    ) -> StdResult<ClaimHistoryResponse> {
+        self.query(querier, &(QueryMsg::ClaimHistory { addr: addr.into(), start_after, limit }))
+    }
+
+    pub fn quote_early_unlock(
+        &self,
+        querier: &QuerierWrapper,
+        addr: impl Into<String>,
+        amount: Uint256
+    ) -> StdResult<EarlyUnlockQuote> {
+        self.query(querier, &(QueryMsg::QuoteEarlyUnlock { addr: addr.into(), amount }))
+    }
+
+    pub fn pending_rewards(
+        &self,
+        querier: &QuerierWrapper,
+        addr: impl Into<String>
+    ) -> StdResult<Vec<Coin>> {
+        self.query(querier, &(QueryMsg::PendingRewards { addr: addr.into() }))
+    }
+
+    pub fn rewards(&self, querier: &QuerierWrapper) -> StdResult<RewardsResponse> {
+        self.query(querier, &(QueryMsg::Rewards {}))
+    }
+
+    pub fn pending_config_changes(
+        &self,
+        querier: &QuerierWrapper,
+        start_after: Option<u64>,
+        limit: Option<u32>
+    ) -> StdResult<PendingConfigChangesResponse> {
+        self.query(querier, &(QueryMsg::PendingConfigChanges { start_after, limit }))
+    }
+
+    pub fn proposal(&self, querier: &QuerierWrapper, id: u64) -> StdResult<Proposal> {
+        self.query(querier, &(QueryMsg::GetProposal { id }))
+    }
+
+    pub fn open_proposals(
+        &self,
+        querier: &QuerierWrapper,
+        start_after: Option<u64>,
+        limit: Option<u32>
+    ) -> StdResult<ProposalsResponse> {
+        self.query(querier, &(QueryMsg::OpenProposals { start_after, limit }))
+    }
 }
@@ -0,0 +1,42 @@
+//! Minimal Injective custom message bindings, shaped like the ones of `injective-cosmwasm`.
+//! Only the token-factory mint used by mint-on-claim mode is covered.
+use schemars::JsonSchema;
+use serde::{ Deserialize, Serialize };
+
+use cosmwasm_std::{ Addr, Coin, CosmosMsg, CustomMsg };
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum InjectiveRoute {
+    Tokenfactory,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum InjectiveMsg {
+    Mint {
+        sender: Addr,
+        amount: Coin,
+        mint_to: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InjectiveMsgWrapper {
+    pub route: InjectiveRoute,
+    pub msg_data: InjectiveMsg,
+}
+
+impl CustomMsg for InjectiveMsgWrapper {}
+
+/// `sender` has to be the admin of the token-factory denom
+pub fn create_mint_tokens_msg(
+    sender: Addr,
+    amount: Coin,
+    mint_to: String
+) -> CosmosMsg<InjectiveMsgWrapper> {
+    CosmosMsg::Custom(InjectiveMsgWrapper {
+        route: InjectiveRoute::Tokenfactory,
+        msg_data: InjectiveMsg::Mint { sender, amount, mint_to },
+    })
+}
@@ -1,28 +1,145 @@
 #[cfg(test)]
 pub mod tests {
-    use crate::{ helpers::CwTemplateContract, ContractError };
+    use crate::{ helpers::VestingContract, ContractError };
+    use crate::injective::{ InjectiveMsg, InjectiveMsgWrapper };
     use crate::msg::InstantiateMsg;
-    use anyhow::Error;
+    use anyhow::{ bail, Error, Result as AnyResult };
+    use serde::de::DeserializeOwned;
 
-    use cosmwasm_std::{ Addr, Coin, Empty, Uint128, Uint256 };
-    use cw_multi_test::{ App, AppBuilder, AppResponse, Contract, ContractWrapper, Executor };
+    use cosmwasm_std::{
+        Addr,
+        Api,
+        Binary,
+        BlockInfo,
+        Coin,
+        CustomMsg,
+        CustomQuery,
+        Empty,
+        Querier,
+        Storage,
+        Uint128,
+        Uint256,
+    };
+    use cw_multi_test::{
+        App,
+        AppBuilder,
+        AppResponse,
+        BankKeeper,
+        BankSudo,
+        Contract,
+        ContractWrapper,
+        CosmosRouter,
+        Executor,
+        Module,
+        WasmKeeper,
+    };
 
-    pub fn contract_template() -> Box<dyn Contract<Empty>> {
+    pub type InjectiveApp = App<
+        BankKeeper,
+        cosmwasm_std::testing::MockApi,
+        cosmwasm_std::testing::MockStorage,
+        TokenFactoryModule,
+        WasmKeeper<InjectiveMsgWrapper, Empty>
+    >;
+
+    /// Handles the token-factory mint the contract sends in mint-on-claim mode
+    pub struct TokenFactoryModule;
+
+    impl Module for TokenFactoryModule {
+        type ExecT = InjectiveMsgWrapper;
+        type QueryT = Empty;
+        type SudoT = Empty;
+
+        fn execute<ExecC, QueryC>(
+            &self,
+            api: &dyn Api,
+            storage: &mut dyn Storage,
+            router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+            block: &BlockInfo,
+            sender: Addr,
+            msg: Self::ExecT
+        ) -> AnyResult<AppResponse>
+            where
+                ExecC: CustomMsg + DeserializeOwned + 'static,
+                QueryC: CustomQuery + DeserializeOwned + 'static
+        {
+            match msg.msg_data {
+                InjectiveMsg::Mint { sender: minter, amount, mint_to } => {
+                    if minter != sender {
+                        bail!("Only denom admin can mint");
+                    }
+
+                    router.sudo(
+                        api,
+                        storage,
+                        block,
+                        (BankSudo::Mint { to_address: mint_to, amount: vec![amount] }).into()
+                    )
+                }
+            }
+        }
+
+        fn query(
+            &self,
+            _api: &dyn Api,
+            _storage: &dyn Storage,
+            _querier: &dyn Querier,
+            _block: &BlockInfo,
+            _request: Self::QueryT
+        ) -> AnyResult<Binary> {
+            bail!("Unexpected custom query")
+        }
+
+        fn sudo<ExecC, QueryC>(
+            &self,
+            _api: &dyn Api,
+            _storage: &mut dyn Storage,
+            _router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+            _block: &BlockInfo,
+            _msg: Self::SudoT
+        ) -> AnyResult<AppResponse>
+            where
+                ExecC: CustomMsg + DeserializeOwned + 'static,
+                QueryC: CustomQuery + DeserializeOwned + 'static
+        {
+            bail!("Unexpected custom sudo")
+        }
+    }
+
+    pub fn contract_template() -> Box<dyn Contract<InjectiveMsgWrapper>> {
         let contract = ContractWrapper::new(
             crate::contract::execute,
             crate::contract::instantiate,
             crate::contract::query
+        ).with_sudo(crate::contract::sudo);
+        Box::new(contract)
+    }
+
+    pub fn grant_nft_template() -> Box<dyn Contract<InjectiveMsgWrapper>> {
+        let contract = ContractWrapper::new_with_empty(
+            cw721_base::entry::execute,
+            cw721_base::entry::instantiate,
+            cw721_base::entry::query
         );
         Box::new(contract)
     }
 
+    pub fn factory_template() -> Box<dyn Contract<InjectiveMsgWrapper>> {
+        let contract = ContractWrapper::new_with_empty(
+            crate::factory::contract::execute,
+            crate::factory::contract::instantiate,
+            crate::factory::contract::query
+        ).with_reply_empty(crate::factory::contract::reply);
+        Box::new(contract)
+    }
+
     pub const ADMIN: &str = "inj1mvnlejy385wy498z7hvmflrsdfludx8ztxsx7m";
     pub const USER: &str = "inj1mvnlejy385wy498z7hvmflrsdfludx8ztxsx7d";
     pub const INJEX_TOKEN: &str = "INJX";
     pub const USDT: &str = "USDT";
 
-    pub fn mock_app() -> App {
-        AppBuilder::new().build(|router, _, storage| {
+    pub fn mock_app() -> InjectiveApp {
+        AppBuilder::new_custom().with_custom(TokenFactoryModule).build(|router, _, storage| {
             router.bank
                 .init_balance(
                     storage,
@@ -66,8 +183,10 @@ pub mod tests {
         })
     }
 
-    pub fn proper_instantiate(should_add_balance_to_contract: bool) -> (App, CwTemplateContract) {
-        let mut app: App = mock_app();
+    pub fn proper_instantiate(
+        should_add_balance_to_contract: bool
+    ) -> (InjectiveApp, VestingContract) {
+        let mut app = mock_app();
         let cw_template_id = app.store_code(contract_template());
 
         let msg = InstantiateMsg {
@@ -76,6 +195,16 @@ pub mod tests {
             lock_periods: Uint256::from_u128(5_u128),
             injex_token: INJEX_TOKEN.to_string(),
             admin: Addr::unchecked(ADMIN).to_string(),
+            voucher_signer: None,
+            extra_denoms: vec![],
+            max_total_allocation: None,
+            treasury: None,
+            mint_cap: None,
+            early_unlock: None,
+            reward_denoms: vec![],
+            min_delay: 3600,
+            approvals: None,
+            dao: None,
         };
 
         let send_funds: &[Coin] = if should_add_balance_to_contract {
@@ -89,7 +218,7 @@ pub mod tests {
             &[]
         };
 
-        let cw_template_contract_addr = app
+        let vesting_contract_addr = app
             .instantiate_contract(
                 cw_template_id,
                 Addr::unchecked(ADMIN),
@@ -100,9 +229,9 @@ pub mod tests {
             )
             .unwrap();
 
-        let cw_template_contract = CwTemplateContract(cw_template_contract_addr);
+        let vesting_contract = VestingContract(vesting_contract_addr);
 
-        (app, cw_template_contract)
+        (app, vesting_contract)
     }
 
     pub fn expect_error(res: Result<AppResponse, Error>, reason: String) -> () {
@@ -1,8 +1,12 @@
 pub mod contract;
 mod error;
+pub mod factory;
+pub mod injective;
 pub mod msg;
 pub mod state;
+#[allow(clippy::useless_format, clippy::useless_vec)]
 mod test;
+#[allow(clippy::unused_unit)]
 pub mod integration_tests;
 pub mod helpers;
 
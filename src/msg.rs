@@ -1,12 +1,30 @@
-use cosmwasm_std::Uint256;
-use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{ Addr, Binary, Coin, Timestamp, Uint128, Uint256 };
+use cw2::ContractVersion;
+use cosmwasm_schema::{ cw_serde, QueryResponses };
 use schemars::JsonSchema;
 use serde::{ Deserialize, Serialize };
 
+use crate::state::{
+    ApprovalConfig,
+    ClaimRecord,
+    ConfigChange,
+    DenomTotals,
+    EarlyUnlockConfig,
+    MerkleAllocation,
+    PenaltyDestination,
+    PendingConfigChange,
+    Proposal,
+    RewardTotals,
+    VoucherSigner,
+};
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct UserVesting {
     pub amount: Uint256,
     pub user: String,
+    // amounts in whitelisted extra denoms, vested on the same schedule as `amount`
+    #[serde(default)]
+    pub extra: Vec<Coin>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -23,23 +41,305 @@ pub struct InstantiateMsg {
     pub instant_claim_percents: Uint256,
     pub lock_minutes: Uint256,
     pub lock_periods: Uint256,
+    pub voucher_signer: Option<VoucherSigner>,
+    #[serde(default)]
+    pub extra_denoms: Vec<String>,
+    #[serde(default)]
+    pub max_total_allocation: Option<Uint256>,
+    #[serde(default)]
+    pub treasury: Option<String>,
+    // enables mint-on-claim mode, can't be changed later
+    #[serde(default)]
+    pub mint_cap: Option<Uint256>,
+    #[serde(default)]
+    pub early_unlock: Option<EarlyUnlockConfig>,
+    #[serde(default)]
+    pub reward_denoms: Vec<String>,
+    // seconds a lock minutes or instant percents change has to be scheduled ahead, positive
+    pub min_delay: u64,
+    #[serde(default)]
+    pub approvals: Option<ApprovalsMsg>,
+    // DAO this contract is a voting module of, the instantiator when unset
+    #[serde(default)]
+    pub dao: Option<String>,
 }
 
 #[cw_serde]
+pub struct ApprovalsMsg {
+    pub approvers: Vec<String>,
+    pub threshold: u32,
+    // seconds a proposal stays open
+    pub proposal_ttl: u64,
+}
+
+/// Allocation signed off-chain by the configured voucher signer.
+/// The signature covers sha256 of the voucher serialized to JSON.
+/// Only a beneficiary without a position can redeem one, so each gets a single voucher.
+#[cw_serde]
+pub struct Voucher {
+    pub contract: String,
+    pub beneficiary: String,
+    pub amount: Uint256,
+    pub vesting_start: Timestamp,
+    pub nonce: u64,
+    pub expires: Timestamp,
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
 pub enum QueryMsg {
-    GetClaimableAmount {
+    #[returns(Uint256)] GetClaimableAmount {
         addr: String,
     },
-    GetInstantClaim {
+    #[returns(Uint256)] GetInstantClaim {
         addr: String,
     },
-    GetVestedAmount {
+    #[returns(Vec<Coin>)] GetExtraClaimable {
         addr: String,
     },
-    GetInjxToken {},
-    GetConfig {},
-    GetTotalClaimed {},
-    GetTotalVested {},
+    #[returns(UserVestingInfo)] GetVestedAmount {
+        addr: String,
+    },
+    #[returns(DenomResponse)] GetInjxToken {},
+    #[returns(ConfigResponse)] GetConfig {},
+    #[returns(Uint256)] GetTotalClaimed {},
+    #[returns(Uint256)] GetTotalVested {},
+    #[returns(TotalsResponse)] GetTotals {},
+    #[returns(bool)] IsPaused {},
+    #[returns(UnallocatedResponse)] GetUnallocated {},
+    #[returns(StatsResponse)] Stats {},
+    #[returns(SolvencyResponse)] CheckSolvency {},
+    #[returns(AuditPositionsResponse)] AuditPositions {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Power of the beneficiary a grant was created for. Tokenized grants carry no power, NFT
+    /// transfers are not reported to this contract so it could not follow the holder
+    #[returns(VotingPowerAtHeightResponse)] VotingPowerAtHeight {
+        address: String,
+        height: Option<u64>,
+    },
+    /// Sum over every grant that is not tokenized
+    #[returns(TotalPowerAtHeightResponse)] TotalPowerAtHeight {
+        height: Option<u64>,
+    },
+    #[returns(InfoResponse)] Info {},
+    #[returns(Addr)] Dao {},
+    #[returns(MerkleAllocation)] GetMerkleAllocation {},
+    #[returns(bool)] IsRegistered {
+        addr: String,
+    },
+    #[returns(bool)] IsVoucherNonceUsed {
+        nonce: u64,
+    },
+    #[returns(GrantResponse)] GetGrant {
+        grant_id: u64,
+    },
+    #[returns(SimulateAtResponse)] SimulateAt {
+        addr: String,
+        timestamp: Timestamp,
+    },
+    #[returns(UnlockScheduleResponse)] UnlockSchedule {
+        addr: String,
+    },
+    #[returns(ClaimHistoryResponse)] ClaimHistory {
+        addr: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    #[returns(EarlyUnlockQuote)] QuoteEarlyUnlock {
+        addr: String,
+        amount: Uint256,
+    },
+    #[returns(Vec<Coin>)] PendingRewards {
+        addr: String,
+    },
+    #[returns(RewardsResponse)] Rewards {},
+    #[returns(PendingConfigChangesResponse)] PendingConfigChanges {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    #[returns(Proposal)] GetProposal {
+        id: u64,
+    },
+    #[returns(ProposalsResponse)] OpenProposals {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+}
+
+#[cw_serde]
+pub struct ProposalsResponse {
+    pub proposals: Vec<Proposal>,
+}
+
+#[cw_serde]
+pub struct PendingConfigChangesResponse {
+    pub changes: Vec<PendingConfigChange>,
+}
+
+#[cw_serde]
+pub struct RewardsResponse {
+    // locked balance of all positions, rewards are distributed pro-rata to it
+    pub total_weight: Uint256,
+    pub rewards: Vec<RewardTotals>,
+}
+
+#[cw_serde]
+pub struct EarlyUnlockQuote {
+    // taken off the locked periods, division remainders included
+    pub amount: Uint256,
+    pub penalty: Uint256,
+    // paid out to the beneficiary
+    pub released: Uint256,
+    pub destination: PenaltyDestination,
+}
+
+#[cw_serde]
+pub struct SimulateAtResponse {
+    pub timestamp: Timestamp,
+    pub claimable: Uint256,
+    pub instant_claimable: Uint256,
+    pub periodic_claimable: Uint256,
+    // redistributed penalties accrued so far, they are claimable at any time
+    pub bonus: Uint256,
+    pub vested: Uint256,
+    pub locked: Uint256,
+    pub next_unlock: Option<Timestamp>,
+}
+
+#[cw_serde]
+pub struct UnlockTranche {
+    pub timestamp: Timestamp,
+    pub amount: Uint256,
+    // sum of this and every earlier tranche
+    pub cumulative: Uint256,
+    pub instant: bool,
+    pub claimed: bool,
+}
+
+#[cw_serde]
+pub struct UnlockScheduleResponse {
+    pub tranches: Vec<UnlockTranche>,
+}
+
+#[cw_serde]
+pub struct ClaimHistoryResponse {
+    pub claims: Vec<ClaimRecord>,
+}
+
+#[cw_serde]
+pub struct ConfigResponse {
+    pub admin: Addr,
+    pub injex_token: String,
+    pub instant_claim_percents: Uint256,
+    pub lock_minutes: Uint256,
+    pub lock_periods: Uint256,
+    pub voucher_signer: Option<VoucherSigner>,
+    pub grant_nft: Option<Addr>,
+    pub extra_denoms: Vec<String>,
+    pub max_total_allocation: Option<Uint256>,
+    pub treasury: Option<Addr>,
+    pub mint_cap: Option<Uint256>,
+    pub early_unlock: Option<EarlyUnlockConfig>,
+    pub reward_denoms: Vec<String>,
+    pub min_delay: u64,
+    pub approvals: Option<ApprovalConfig>,
+}
+
+#[cw_serde]
+pub struct DenomResponse {
+    pub denom: String,
+}
+
+#[cw_serde]
+pub struct TotalsResponse {
+    pub total_claimed: Uint256,
+    pub total_vested: Uint256,
+    pub extra: Vec<DenomTotals>,
+}
+
+#[cw_serde]
+pub struct UnallocatedResponse {
+    // deposited balance not owed to any beneficiary, per denom
+    pub unallocated: Vec<Coin>,
+    pub total_allocated: Uint256,
+    pub max_total_allocation: Option<Uint256>,
+    pub remaining_allocation: Option<Uint256>,
+}
+
+#[cw_serde]
+pub struct GrantResponse {
+    pub grant_id: u64,
+    pub user: Addr,
+}
+
+#[cw_serde]
+pub struct StatsResponse {
+    pub beneficiary_count: u64,
+    pub fully_claimed_count: u64,
+    pub total_vested: Uint256,
+    pub total_claimed: Uint256,
+    pub unlocked_unclaimed: Uint256,
+    pub locked: Uint256,
+    pub instant_liability: Uint256,
+    pub balance: Uint256,
+    // mint_cap - total_minted in mint mode, counted as backing next to the balance
+    pub mintable: Uint256,
+    pub surplus: Uint256,
+    pub deficit: Uint256,
+}
+
+#[cw_serde]
+pub struct SolvencyResponse {
+    pub balance: Uint256,
+    // mint_cap - total_minted in mint mode, counted as backing next to the balance
+    pub mintable: Uint256,
+    // total_vested - total_claimed
+    pub outstanding: Uint256,
+    // funded through a merkle root but not registered by beneficiaries yet
+    pub unregistered_merkle: Uint256,
+    pub solvent: bool,
+    pub shortfall: Uint256,
+}
+
+#[cw_serde]
+pub enum PositionIssue {
+    ClaimedExceedsGrant,
+    ClaimedNotPeriodMultiple,
+    InstantExceedsGrant,
+    ZeroPeriodAmount,
+}
+
+#[cw_serde]
+pub struct PositionAudit {
+    pub user: Addr,
+    pub granted: Uint256,
+    pub claimed: Uint256,
+    pub liability: Uint256,
+    pub issues: Vec<PositionIssue>,
+}
+
+#[cw_serde]
+pub struct AuditPositionsResponse {
+    pub positions: Vec<PositionAudit>,
+}
+
+#[cw_serde]
+pub struct VotingPowerAtHeightResponse {
+    pub power: Uint128,
+    pub height: u64,
+}
+
+#[cw_serde]
+pub struct TotalPowerAtHeightResponse {
+    pub power: Uint128,
+    pub height: u64,
+}
+
+#[cw_serde]
+pub struct InfoResponse {
+    pub info: ContractVersion,
 }
 
 #[cw_serde]
@@ -51,10 +351,123 @@ pub enum ExecuteMsg {
     ChangeAdmin {
         new_admin: String,
     },
+    /// Timelocked, always fails. Schedule a ConfigChange::InstantClaimPercents instead
     ChangeInstantClaimPercents {
         new_percents: Uint256,
     },
+    /// Timelocked, always fails. Schedule a ConfigChange::LockMinutes instead
     ChangeLockMinutes {
         new_lock_minutes: Uint256,
     },
+    /// Reserves `total_amount` of the pool for the beneficiaries of the tree. Attached funds
+    /// are deposited first, so the root can be funded in the same message or not at all. In
+    /// mint mode it is reserved under the mint cap and takes no funds
+    RegisterMerkleRoot {
+        merkle_root: String,
+        total_amount: Uint256,
+    },
+    /// Leaves of the tree are sha256 of `"{address}:{amount}:{vesting_start}"`, with the
+    /// vesting start in nanoseconds as it is serialized
+    RegisterVesting {
+        amount: Uint256,
+        vesting_start: Timestamp,
+        proof: Vec<String>,
+    },
+    ChangeVoucherSigner {
+        new_signer: Option<VoucherSigner>,
+    },
+    RedeemVoucher {
+        voucher: Voucher,
+        signature: Binary,
+    },
+    SetGrantNft {
+        nft_contract: String,
+    },
+    ClaimGrant {
+        grant_id: u64,
+    },
+    SetExtraDenoms {
+        denoms: Vec<String>,
+    },
+    Deposit {},
+    SetMaxTotalAllocation {
+        max_total_allocation: Option<Uint256>,
+    },
+    ChangeTreasury {
+        new_treasury: Option<String>,
+    },
+    /// Spreads `amount` evenly over the periods that have not unlocked yet, allocated from
+    /// the unallocated pool (attached funds are deposited first)
+    IncreaseGrant {
+        user: String,
+        grant_id: u64,
+        amount: Uint256,
+    },
+    /// Takes `amount` off the periods that have not unlocked yet and refunds it to the treasury
+    DecreaseGrant {
+        user: String,
+        grant_id: u64,
+        amount: Uint256,
+    },
+    SetEarlyUnlock {
+        early_unlock: Option<EarlyUnlockConfig>,
+    },
+    /// Releases `amount` of the sender's locked tokens now, minus a penalty, see QuoteEarlyUnlock
+    EarlyUnlock {
+        amount: Uint256,
+    },
+    SetRewardDenoms {
+        denoms: Vec<String>,
+    },
+    /// Shares the attached funds among all positions pro-rata to their locked balance at the
+    /// time. Unlocked tokens earn nothing from then on, whether they were claimed or not
+    DistributeRewards {},
+    /// Claims rewards of the sender's position, or of a tokenized grant held by the sender
+    ClaimRewards {
+        grant_id: Option<u64>,
+    },
+    ScheduleConfigChange {
+        change: ConfigChange,
+        effective_time: Timestamp,
+    },
+    CancelConfigChange {
+        id: u64,
+    },
+    /// Anyone can apply a scheduled change once its effective time has passed
+    ExecuteConfigChange {
+        id: u64,
+    },
+    SetApprovals {
+        approvals: Option<ApprovalsMsg>,
+    },
+    /// Proposes a privileged message, it runs as the admin without funds once approved, so
+    /// allocations are made from deposited funds
+    Propose {
+        msg: Box<ExecuteMsg>,
+    },
+    Approve {
+        id: u64,
+    },
+    ExecuteProposal {
+        id: u64,
+    },
+}
+
+/// Overrides available to chain governance, so a lost admin key can't freeze the program
+#[cw_serde]
+pub enum SudoMsg {
+    ReplaceAdmin {
+        new_admin: String,
+    },
+    SetPaused {
+        paused: bool,
+    },
+    /// Takes off everything still locked, in every denom of the grant. It is shared among the
+    /// remaining positions with `redistribute` and refunded like DecreaseGrant otherwise, extra
+    /// denoms are shared as rewards
+    RevokeGrant {
+        user: String,
+        #[serde(default)]
+        redistribute: bool,
+    },
 }
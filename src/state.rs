@@ -1,18 +1,29 @@
 use schemars::JsonSchema;
 use serde::{ Deserialize, Serialize };
 
-use cosmwasm_std::{ Addr, Timestamp, Uint256 };
-use cw_storage_plus::{ Item, Map };
+use cosmwasm_std::{ Addr, Binary, Coin, Decimal256, Timestamp, Uint128, Uint256 };
+use cw_storage_plus::{ Item, Map, SnapshotItem, SnapshotMap, Strategy };
+
+use crate::msg::ExecuteMsg;
 
 // 100%
 pub const PERCENTS: Uint256 = Uint256::from_u128(10_000_u128);
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
 pub struct UserClaims {
     pub amount: Uint256,
     pub last_claimed: Timestamp,
     pub amount_claimed: Uint256,
     pub init_vesting: Timestamp,
+    // instant part as granted, INSTANT_CLAIMABLE_AMOUNT only keeps what is left of it
+    #[serde(default)]
+    pub instant_amount: Uint256,
+    // Set when the grant is amended: periods unlocked before the amendment and what they
+    // released in total, the remaining periods unlock `amount` each
+    #[serde(default)]
+    pub base_period: u64,
+    #[serde(default)]
+    pub base_vested: Uint256,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -21,12 +32,239 @@ pub struct Config {
     pub instant_claim_percents: Uint256,
     pub lock_minutes: Uint256,
     pub lock_periods: Uint256,
+    #[serde(default)]
+    pub voucher_signer: Option<VoucherSigner>,
+    // cw721 contract minting one token per grant, its holder is entitled to claim the grant
+    #[serde(default)]
+    pub grant_nft: Option<Addr>,
+    // denoms besides injex_token that grants may carry, vested on the same schedule
+    #[serde(default)]
+    pub extra_denoms: Vec<String>,
+    // cap on everything ever allocated in injex_token, see total_vested
+    #[serde(default)]
+    pub max_total_allocation: Option<Uint256>,
+    // receives refunds of decreased grants, they stay in the unallocated pool when unset
+    #[serde(default)]
+    pub treasury: Option<Addr>,
+    // Mint-on-claim mode: grants are obligations only and claims mint injex_token through the
+    // token-factory (the contract has to be the denom admin), up to this many tokens in total
+    #[serde(default)]
+    pub mint_cap: Option<Uint256>,
+    // EarlyUnlock is disabled when unset
+    #[serde(default)]
+    pub early_unlock: Option<EarlyUnlockConfig>,
+    // denoms DistributeRewards accepts
+    #[serde(default)]
+    pub reward_denoms: Vec<String>,
+    // Seconds a ConfigChange has to be scheduled ahead, always positive so the settings it
+    // covers can never be changed directly
+    pub min_delay: u64,
+    // Privileged actions need a proposal approved by `threshold` approvers while set
+    #[serde(default)]
+    pub approvals: Option<ApprovalConfig>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ApprovalConfig {
+    pub approvers: Vec<Addr>,
+    pub threshold: u32,
+    // seconds a proposal stays open
+    pub proposal_ttl: u64,
+}
+
+// Privileged `msg` run as the admin once approved, see ExecuteMsg::Propose
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Proposal {
+    pub id: u64,
+    pub proposer: Addr,
+    pub msg: ExecuteMsg,
+    pub approvals: Vec<Addr>,
+    pub expires: Timestamp,
+    pub executed: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigChange {
+    LockMinutes {
+        new_lock_minutes: Uint256,
+    },
+    InstantClaimPercents {
+        new_percents: Uint256,
+    },
+    MinDelay {
+        new_min_delay: u64,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingConfigChange {
+    pub id: u64,
+    pub change: ConfigChange,
+    pub effective_time: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EarlyUnlockConfig {
+    // penalty for unlocking at the very start of the schedule, it decays linearly to zero
+    pub max_penalty_percents: Uint256,
+    pub destination: PenaltyDestination,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PenaltyDestination {
+    Burn,
+    Treasury,
+    // shared among the other positions pro-rata to their locked balance, kept by the contract
+    // as unallocated funds when none of them has anything locked
+    Redistribute,
+}
+
+// Position in one of the extra denoms, it follows the schedule of the user's UserClaims
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct DenomClaims {
+    pub amount: Uint256,
+    pub amount_claimed: Uint256,
+    pub instant_amount: Uint256,
+    pub instant_claimable: Uint256,
+}
+
+// Rewards distributed in one denom, `index` is the amount per unit of locked balance
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RewardTotals {
+    pub denom: String,
+    pub index: Decimal256,
+    pub total_distributed: Uint256,
+    pub total_claimed: Uint256,
+}
+
+// RewardTotals::index as of the position's last checkpoint, the rewards earned up to it and
+// when it was taken, the locked balance is followed through the unlocks from there
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct RewardCheckpoint {
+    pub index: Decimal256,
+    pub pending: Uint256,
+    #[serde(default)]
+    pub time: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DenomTotals {
+    pub denom: String,
+    pub total_vested: Uint256,
+    pub total_claimed: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VoucherSigner {
+    Secp256k1 {
+        public_key: Binary,
+    },
+    Ed25519 {
+        public_key: Binary,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct State {
     pub total_claimed: Uint256,
     pub total_vested: Uint256,
+    #[serde(default)]
+    pub beneficiary_count: u64,
+    #[serde(default)]
+    pub fully_claimed_count: u64,
+    // unclaimed instant parts of all positions
+    #[serde(default)]
+    pub instant_outstanding: Uint256,
+    // periodic amounts claimed from positions that still exist
+    #[serde(default)]
+    pub periodic_claimed: Uint256,
+    #[serde(default)]
+    pub extra_totals: Vec<DenomTotals>,
+    // Sum of base_vested - base_period * amount over amended positions, split by sign. Added
+    // to what the cohorts unlock, it keeps the stats exact for amended grants
+    #[serde(default)]
+    pub periodic_offset_plus: Uint256,
+    #[serde(default)]
+    pub periodic_offset_minus: Uint256,
+    #[serde(default)]
+    pub total_minted: Uint256,
+    // Forfeited tokens shared per unit of locked balance, like RewardTotals::index
+    #[serde(default)]
+    pub bonus_index: Decimal256,
+    // shared but not claimed yet, it is part of total_vested until claimed
+    #[serde(default)]
+    pub bonus_outstanding: Uint256,
+    #[serde(default)]
+    pub reward_totals: Vec<RewardTotals>,
+    // Locked balance of all positions as of the last unlock released, see UNLOCK_SCHEDULE
+    #[serde(default)]
+    pub total_locked: Uint256,
+}
+
+impl State {
+    pub fn denom_totals(&mut self, denom: &str) -> &mut DenomTotals {
+        match self.extra_totals.iter().position(|totals| totals.denom == denom) {
+            Some(index) => &mut self.extra_totals[index],
+            None => {
+                self.extra_totals.push(DenomTotals {
+                    denom: denom.to_string(),
+                    total_vested: Uint256::zero(),
+                    total_claimed: Uint256::zero(),
+                });
+
+                self.extra_totals.last_mut().unwrap()
+            }
+        }
+    }
+
+    pub fn reward_totals(&mut self, denom: &str) -> &mut RewardTotals {
+        match self.reward_totals.iter().position(|totals| totals.denom == denom) {
+            Some(index) => &mut self.reward_totals[index],
+            None => {
+                self.reward_totals.push(RewardTotals {
+                    denom: denom.to_string(),
+                    index: Decimal256::zero(),
+                    total_distributed: Uint256::zero(),
+                    total_claimed: Uint256::zero(),
+                });
+
+                self.reward_totals.last_mut().unwrap()
+            }
+        }
+    }
+
+    // Rewards distributed in `denom` that were not claimed yet
+    pub fn rewards_outstanding(&self, denom: &str) -> Uint256 {
+        self.reward_totals
+            .iter()
+            .find(|totals| totals.denom == denom)
+            .map(|totals| totals.total_distributed - totals.total_claimed)
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MerkleAllocation {
+    pub merkle_root: String,
+    pub total_amount: Uint256,
+    pub registered_amount: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClaimRecord {
+    pub id: u64,
+    pub timestamp: Timestamp,
+    pub height: u64,
+    pub instant_amount: Uint256,
+    pub periodic_amount: Uint256,
+    pub recipient: Addr,
+    #[serde(default)]
+    pub extra: Vec<Coin>,
+    #[serde(default)]
+    pub bonus_amount: Uint256,
 }
 
 pub const CONFIG: Item<Config> = Item::new("config");
@@ -34,7 +272,77 @@ pub const CONFIG: Item<Config> = Item::new("config");
 pub const STATE: Item<State> = Item::new("state");
 
 pub const ADMIN: Item<Addr> = Item::new("admin");
+pub const DAO: Item<Addr> = Item::new("dao");
+
+// set by chain governance through sudo, blocks every execute message
+pub const PAUSED: Item<bool> = Item::new("paused");
 
 pub const CLAIMABLE_AMOUNT: Map<Addr, UserClaims> = Map::new("claimable_amount");
 
 pub const INSTANT_CLAIMABLE_AMOUNT: Map<Addr, Uint256> = Map::new("instant_claimable_amount");
+
+pub const EXTRA_CLAIMS: Map<(Addr, String), DenomClaims> = Map::new("extra_claims");
+
+pub const PENDING_CONFIG_CHANGES: Map<u64, PendingConfigChange> = Map::new(
+    "pending_config_changes"
+);
+
+pub const CONFIG_CHANGE_COUNT: Item<u64> = Item::new("config_change_count");
+
+pub const PROPOSALS: Map<u64, Proposal> = Map::new("proposals");
+
+pub const PROPOSAL_COUNT: Item<u64> = Item::new("proposal_count");
+
+pub const REWARD_CHECKPOINTS: Map<(Addr, String), RewardCheckpoint> = Map::new(
+    "reward_checkpoints"
+);
+
+// RewardTotals::index after the last distribution in a given second, keyed by denom and time
+pub const REWARD_INDEX_HISTORY: Map<(String, u64), Decimal256> = Map::new(
+    "reward_index_history"
+);
+
+// State::bonus_index as of each position's last checkpoint and the bonus earned up to it
+pub const BONUS_CHECKPOINTS: Map<Addr, RewardCheckpoint> = Map::new("bonus_checkpoints");
+
+// State::bonus_index after the last forfeit in a given second
+pub const BONUS_INDEX_HISTORY: Map<u64, Decimal256> = Map::new("bonus_index_history");
+
+pub const MERKLE_ALLOCATION: Item<MerkleAllocation> = Item::new("merkle_allocation");
+
+pub const MERKLE_REGISTERED: Map<Addr, bool> = Map::new("merkle_registered");
+
+pub const GRANT_COUNT: Item<u64> = Item::new("grant_count");
+
+pub const GRANTS: Map<u64, Addr> = Map::new("grants");
+
+pub const GRANT_IDS: Map<Addr, u64> = Map::new("grant_ids");
+
+// Sum of per-period amounts of all positions sharing a vesting start (in seconds), lets
+// locked and unlocked totals be computed without iterating over every position
+pub const VESTING_COHORTS: Map<u64, Uint256> = Map::new("vesting_cohorts");
+
+// Locked balance released at a given unlock time (in seconds), it is taken off
+// State::total_locked once the time has passed and removed
+pub const UNLOCK_SCHEDULE: Map<u64, Uint256> = Map::new("unlock_schedule");
+
+pub const CLAIM_COUNT: Map<Addr, u64> = Map::new("claim_count");
+
+pub const CLAIM_HISTORY: Map<(Addr, u64), ClaimRecord> = Map::new("claim_history");
+
+pub const USED_VOUCHER_NONCES: Map<u64, bool> = Map::new("used_voucher_nonces");
+
+// Locked plus unclaimed balance of every user, checkpointed per block for DAO voting
+pub const VOTING_POWER: SnapshotMap<Addr, Uint128> = SnapshotMap::new(
+    "voting_power",
+    "voting_power__checkpoints",
+    "voting_power__changelog",
+    Strategy::EveryBlock
+);
+
+pub const TOTAL_POWER: SnapshotItem<Uint128> = SnapshotItem::new(
+    "total_power",
+    "total_power__checkpoints",
+    "total_power__changelog",
+    Strategy::EveryBlock
+);
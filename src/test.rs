@@ -1,18 +1,63 @@
 #[cfg(test)]
 mod tests {
-    use cosmwasm_std::{ Addr, BlockInfo, Coin, Uint128, Uint256 };
-    use cw_multi_test::Executor;
+    use std::str::FromStr;
+
+    use cosmwasm_std::{
+        to_json_vec,
+        Addr,
+        Binary,
+        BlockInfo,
+        Coin,
+        Empty,
+        Timestamp,
+        Uint128,
+        Uint256,
+    };
+    use cw721::OwnerOfResponse;
+    use cw_multi_test::{ AppResponse, Executor };
+    use ed25519_zebra::{ SigningKey, VerificationKeyBytes };
+    use sha2::{ Digest, Sha256 };
 
     use crate::integration_tests::tests::{
+        contract_template,
         expect_error,
+        factory_template,
+        grant_nft_template,
+        InjectiveApp,
+        mock_app,
         proper_instantiate,
         ADMIN,
         INJEX_TOKEN,
         USDT,
         USER,
     };
-    use crate::msg::{ ExecuteMsg, QueryMsg, UserVesting, UserVestingInfo };
-    use crate::state::{ Config, PERCENTS };
+    use crate::factory::helpers::FactoryContract;
+    use crate::helpers::VestingContract;
+    use crate::msg::{
+        ApprovalsMsg,
+        ConfigResponse,
+        DenomResponse,
+        ExecuteMsg,
+        GrantResponse,
+        InstantiateMsg,
+        QueryMsg,
+        SudoMsg,
+        TotalPowerAtHeightResponse,
+        TotalsResponse,
+        UnlockTranche,
+        UserVesting,
+        UserVestingInfo,
+        Voucher,
+        VotingPowerAtHeightResponse,
+    };
+    use crate::state::{
+        ConfigChange,
+        EarlyUnlockConfig,
+        MerkleAllocation,
+        PenaltyDestination,
+        VoucherSigner,
+        PERCENTS,
+    };
 
     #[test]
     fn proper_initialization() {
@@ -34,14 +79,44 @@ mod tests {
 
         let config = app.wrap().query_wasm_smart(contract.addr(), &config_msg).unwrap();
 
+        let totals: TotalsResponse = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &QueryMsg::GetTotals {})
+            .unwrap();
+
+        let denom: DenomResponse = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &QueryMsg::GetInjxToken {})
+            .unwrap();
+
         assert_eq!(Uint256::zero(), total_claimed);
         assert_eq!(Uint256::zero(), total_vested);
         assert_eq!(
-            Config {
+            TotalsResponse {
+                total_claimed: Uint256::zero(),
+                total_vested: Uint256::zero(),
+                extra: vec![],
+            },
+            totals
+        );
+        assert_eq!(INJEX_TOKEN.to_string(), denom.denom);
+        assert_eq!(
+            ConfigResponse {
+                admin: Addr::unchecked(ADMIN),
                 instant_claim_percents: Uint256::from_u128(1500_u128), // 15%
                 lock_minutes: Uint256::from_u128(5_u128),
                 lock_periods: Uint256::from_u128(5_u128),
                 injex_token: INJEX_TOKEN.to_string(),
+                voucher_signer: None,
+                grant_nft: None,
+                extra_denoms: vec![],
+                max_total_allocation: None,
+                treasury: None,
+                mint_cap: None,
+                early_unlock: None,
+                reward_denoms: vec![],
+                min_delay: 3600,
+                approvals: None,
             },
             config
         );
@@ -63,15 +138,21 @@ mod tests {
 
     #[test]
     fn vest_no_funds() {
-        let (mut app, contract) = proper_instantiate(true);
+        let (mut app, contract) = proper_instantiate(false);
 
-        let msg = ExecuteMsg::VestTokens { users: vec![] };
+        let msg = ExecuteMsg::VestTokens {
+            users: vec![UserVesting {
+                amount: Uint256::from_u128(1000_u128),
+                user: USER.to_string(),
+                extra: vec![],
+            }],
+        };
 
         let res = app.execute_contract(Addr::unchecked(ADMIN), contract.addr(), &msg, &[]);
 
         assert!(res.is_err());
 
-        let error_message = format!("Invalid funds were provided");
+        let error_message = format!("Insufficient unallocated funds");
         expect_error(res, error_message);
     }
 
@@ -109,6 +190,7 @@ mod tests {
             users: vec![UserVesting {
                 amount: Uint256::zero(),
                 user: USER.to_string(),
+                extra: vec![],
             }],
         };
         {
@@ -138,6 +220,7 @@ mod tests {
             users: vec![UserVesting {
                 amount: Uint256::zero(),
                 user: USER.to_string(),
+                extra: vec![],
             }],
         };
         {
@@ -161,12 +244,13 @@ mod tests {
 
     #[test]
     fn vest_invalid_funds_amount() {
-        let (mut app, contract) = proper_instantiate(true);
+        let (mut app, contract) = proper_instantiate(false);
 
         let msg = ExecuteMsg::VestTokens {
             users: vec![UserVesting {
                 amount: Uint256::from_u128(1001_u128),
                 user: USER.to_string(),
+                extra: vec![],
             }],
         };
         {
@@ -184,7 +268,7 @@ mod tests {
 
         assert!(res.is_err());
 
-        let error_message = format!("Invalid funds were provided");
+        let error_message = format!("Insufficient unallocated funds");
         expect_error(res, error_message);
     }
 
@@ -198,6 +282,7 @@ mod tests {
             users: vec![UserVesting {
                 amount: Uint256::from_uint128(amount),
                 user: USER.to_string(),
+                extra: vec![],
             }],
         };
         let balance = app.wrap().query_balance(ADMIN.to_string(), INJEX_TOKEN.to_string()).unwrap();
@@ -262,6 +347,7 @@ mod tests {
             users: vec![UserVesting {
                 amount: Uint256::from_uint128(amount),
                 user: USER.to_string(),
+                extra: vec![],
             }],
         };
         let balance = app.wrap().query_balance(ADMIN.to_string(), INJEX_TOKEN.to_string()).unwrap();
@@ -344,6 +430,7 @@ mod tests {
             users: vec![UserVesting {
                 amount: Uint256::from_uint128(amount),
                 user: USER.to_string(),
+                extra: vec![],
             }],
         };
         let balance = app.wrap().query_balance(ADMIN.to_string(), INJEX_TOKEN.to_string()).unwrap();
@@ -444,6 +531,7 @@ mod tests {
             users: vec![UserVesting {
                 amount: Uint256::from_uint128(amount),
                 user: USER.to_string(),
+                extra: vec![],
             }],
         };
         let balance = app.wrap().query_balance(ADMIN.to_string(), INJEX_TOKEN.to_string()).unwrap();
@@ -570,6 +658,7 @@ mod tests {
             users: vec![UserVesting {
                 amount: Uint256::from_uint128(amount),
                 user: USER.to_string(),
+                extra: vec![],
             }],
         };
         let balance = app.wrap().query_balance(ADMIN.to_string(), INJEX_TOKEN.to_string()).unwrap();
@@ -697,6 +786,7 @@ mod tests {
             users: vec![UserVesting {
                 amount: Uint256::from_uint128(amount),
                 user: USER.to_string(),
+                extra: vec![],
             }],
         };
         let balance = app.wrap().query_balance(ADMIN.to_string(), INJEX_TOKEN.to_string()).unwrap();
@@ -820,6 +910,7 @@ mod tests {
             users: vec![UserVesting {
                 amount: Uint256::from_uint128(amount),
                 user: USER.to_string(),
+                extra: vec![],
             }],
         };
         let balance = app.wrap().query_balance(ADMIN.to_string(), INJEX_TOKEN.to_string()).unwrap();
@@ -943,6 +1034,7 @@ mod tests {
             users: vec![UserVesting {
                 amount: Uint256::from_uint128(amount),
                 user: USER.to_string(),
+                extra: vec![],
             }],
         };
         let balance = app.wrap().query_balance(ADMIN.to_string(), INJEX_TOKEN.to_string()).unwrap();
@@ -1104,6 +1196,7 @@ mod tests {
             users: vec![UserVesting {
                 amount: Uint256::from_uint128(amount),
                 user: USER.to_string(),
+                extra: vec![],
             }],
         };
         let balance = app.wrap().query_balance(ADMIN.to_string(), INJEX_TOKEN.to_string()).unwrap();
@@ -1237,4 +1330,2696 @@ mod tests {
         let error_message = format!("Cannot claim");
         expect_error(res, error_message);
     }
+
+    #[test]
+    fn voting_power_snapshots() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let amount = Uint128::new(1_000_000);
+
+        let msg = ExecuteMsg::VestTokens {
+            users: vec![UserVesting {
+                amount: Uint256::from_uint128(amount),
+                user: USER.to_string(),
+                extra: vec![],
+            }],
+        };
+
+        let res = app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract.addr(),
+            &msg,
+            &[Coin {
+                denom: INJEX_TOKEN.to_string(),
+                amount,
+            }]
+        );
+
+        assert!(res.is_ok());
+
+        let vest_height = app.block_info().height;
+
+        app.update_block(|block| {
+            block.height += 1;
+        });
+
+        let power_msg = QueryMsg::VotingPowerAtHeight { address: USER.to_string(), height: None };
+        let total_power_msg = QueryMsg::TotalPowerAtHeight { height: None };
+
+        let power: VotingPowerAtHeightResponse = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &power_msg)
+            .unwrap();
+
+        let total_power: TotalPowerAtHeightResponse = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &total_power_msg)
+            .unwrap();
+
+        assert_eq!(power.power, amount);
+        assert_eq!(power.height, vest_height + 1);
+        assert_eq!(total_power.power, amount);
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &ExecuteMsg::Claim {},
+            &[]
+        );
+
+        assert!(res.is_ok());
+
+        let claim_height = app.block_info().height;
+
+        app.update_block(|block| {
+            block.height += 1;
+        });
+
+        let instant_amount =
+            (Uint256::from_uint128(amount) * Uint256::from_u128(1500_u128)) / PERCENTS;
+        let expected_power = Uint128::from_str(
+            &(Uint256::from_uint128(amount) - instant_amount).to_string()
+        ).unwrap();
+
+        let power: VotingPowerAtHeightResponse = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &power_msg)
+            .unwrap();
+
+        let total_power: TotalPowerAtHeightResponse = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &total_power_msg)
+            .unwrap();
+
+        assert_eq!(power.power, expected_power);
+        assert_eq!(total_power.power, expected_power);
+
+        let historical_power_msg = QueryMsg::VotingPowerAtHeight {
+            address: USER.to_string(),
+            height: Some(claim_height),
+        };
+        let historical_total_msg = QueryMsg::TotalPowerAtHeight { height: Some(claim_height) };
+        let before_vest_msg = QueryMsg::TotalPowerAtHeight { height: Some(vest_height) };
+
+        let historical_power: VotingPowerAtHeightResponse = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &historical_power_msg)
+            .unwrap();
+
+        let historical_total: TotalPowerAtHeightResponse = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &historical_total_msg)
+            .unwrap();
+
+        let before_vest: TotalPowerAtHeightResponse = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &before_vest_msg)
+            .unwrap();
+
+        assert_eq!(historical_power.power, amount);
+        assert_eq!(historical_total.power, amount);
+        assert_eq!(before_vest.power, Uint128::zero());
+
+        // instantiated without a dao, so the instantiator is reported
+        assert_eq!(contract.dao(&app.wrap()).unwrap(), Addr::unchecked(ADMIN));
+    }
+
+    fn merkle_leaf(addr: &str, amount: u128, vesting_start: Timestamp) -> [u8; 32] {
+        Sha256::digest(format!("{}:{}:{}", addr, amount, vesting_start.nanos()).as_bytes()).into()
+    }
+
+    fn merkle_parent(left: [u8; 32], right: [u8; 32]) -> [u8; 32] {
+        let mut hashes = [left, right];
+        hashes.sort_unstable();
+
+        Sha256::digest(hashes.concat()).into()
+    }
+
+    #[test]
+    fn merkle_register_vesting() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let vesting_start = app.block_info().time;
+        let user_leaf = merkle_leaf(USER, 1_000_000, vesting_start);
+        let admin_leaf = merkle_leaf(ADMIN, 500_000, vesting_start);
+        let root = merkle_parent(user_leaf, admin_leaf);
+
+        // the pool only holds 1_000_000_000_000_000_000_000
+        let msg = ExecuteMsg::RegisterMerkleRoot {
+            merkle_root: hex::encode(root),
+            total_amount: Uint256::from_u128(2_000_000_000_000_000_000_000_u128),
+        };
+
+        let res = app.execute_contract(Addr::unchecked(ADMIN), contract.addr(), &msg, &[]);
+
+        expect_error(res, "Insufficient unallocated funds".to_string());
+
+        let msg = ExecuteMsg::RegisterMerkleRoot {
+            merkle_root: hex::encode(root),
+            total_amount: Uint256::from_u128(1_500_000_u128),
+        };
+
+        let res = app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract.addr(),
+            &msg,
+            &[Coin {
+                denom: INJEX_TOKEN.to_string(),
+                amount: Uint128::new(1_500_000),
+            }]
+        );
+
+        assert!(res.is_ok());
+
+        let is_registered_msg = QueryMsg::IsRegistered { addr: USER.to_string() };
+
+        let is_registered: bool = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &is_registered_msg)
+            .unwrap();
+
+        assert!(!is_registered);
+
+        // the schedule is part of the leaf
+        let msg = ExecuteMsg::RegisterVesting {
+            amount: Uint256::from_u128(1_000_000_u128),
+            vesting_start: vesting_start.plus_seconds(1),
+            proof: vec![hex::encode(admin_leaf)],
+        };
+
+        let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &msg, &[]);
+
+        expect_error(res, "Invalid merkle proof".to_string());
+
+        let msg = ExecuteMsg::RegisterVesting {
+            amount: Uint256::from_u128(1_000_000_u128),
+            vesting_start,
+            proof: vec![hex::encode(admin_leaf)],
+        };
+
+        let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &msg, &[]);
+
+        assert!(res.is_ok());
+
+        let is_registered: bool = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &is_registered_msg)
+            .unwrap();
+
+        assert!(is_registered);
+
+        let claimable_amount_msg = QueryMsg::GetClaimableAmount { addr: USER.to_string() };
+
+        let claimable_amount: Uint256 = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &claimable_amount_msg)
+            .unwrap();
+
+        assert_eq!(claimable_amount, Uint256::from_u128(150_000_u128));
+
+        let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &msg, &[]);
+
+        let error_message = "Already registered".to_string();
+        expect_error(res, error_message);
+
+        let msg = ExecuteMsg::RegisterVesting {
+            amount: Uint256::from_u128(600_000_u128),
+            vesting_start,
+            proof: vec![hex::encode(user_leaf)],
+        };
+
+        let res = app.execute_contract(Addr::unchecked(ADMIN), contract.addr(), &msg, &[]);
+
+        let error_message = "Invalid merkle proof".to_string();
+        expect_error(res, error_message);
+
+        // registering must not replace a grant the user already vests
+        let msg = ExecuteMsg::VestTokens {
+            users: vec![UserVesting {
+                amount: Uint256::from_u128(1_000_000_u128),
+                user: ADMIN.to_string(),
+                extra: vec![],
+            }],
+        };
+
+        app.execute_contract(Addr::unchecked(ADMIN), contract.addr(), &msg, &[]).unwrap();
+
+        let msg = ExecuteMsg::RegisterVesting {
+            amount: Uint256::from_u128(500_000_u128),
+            vesting_start,
+            proof: vec![hex::encode(user_leaf)],
+        };
+
+        let res = app.execute_contract(Addr::unchecked(ADMIN), contract.addr(), &msg, &[]);
+
+        expect_error(res, "User already has a vesting position".to_string());
+
+        let claimable_amount_msg = QueryMsg::GetClaimableAmount { addr: ADMIN.to_string() };
+
+        let claimable_amount: Uint256 = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &claimable_amount_msg)
+            .unwrap();
+
+        assert_eq!(claimable_amount, Uint256::from_u128(150_000_u128));
+
+        let allocation: MerkleAllocation = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &QueryMsg::GetMerkleAllocation {})
+            .unwrap();
+
+        assert_eq!(allocation.registered_amount, Uint256::from_u128(1_000_000_u128));
+        assert_eq!(allocation.total_amount, Uint256::from_u128(1_500_000_u128));
+    }
+
+    fn sign_voucher(signing_key: &SigningKey, voucher: &Voucher) -> Binary {
+        let message_hash = Sha256::digest(to_json_vec(voucher).unwrap());
+        let signature: [u8; 64] = signing_key.sign(&message_hash).into();
+
+        Binary::from(signature.to_vec())
+    }
+
+    #[test]
+    fn redeem_voucher() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let signing_key = SigningKey::from([7u8; 32]);
+        let public_key = VerificationKeyBytes::from(&signing_key);
+
+        let msg = ExecuteMsg::ChangeVoucherSigner {
+            new_signer: Some(VoucherSigner::Ed25519 {
+                public_key: Binary::from(public_key.as_ref().to_vec()),
+            }),
+        };
+
+        let res = app.execute_contract(Addr::unchecked(ADMIN), contract.addr(), &msg, &[]);
+
+        assert!(res.is_ok());
+
+        let voucher = Voucher {
+            contract: contract.addr().to_string(),
+            beneficiary: USER.to_string(),
+            amount: Uint256::from_u128(1_000_000_u128),
+            vesting_start: app.block_info().time,
+            nonce: 1,
+            expires: app.block_info().time.plus_minutes(60),
+        };
+
+        let msg = ExecuteMsg::RedeemVoucher {
+            voucher: voucher.clone(),
+            signature: sign_voucher(&signing_key, &voucher),
+        };
+
+        let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &msg, &[]);
+
+        assert!(res.is_ok());
+
+        let claimable_amount_msg = QueryMsg::GetClaimableAmount { addr: USER.to_string() };
+
+        let claimable_amount: Uint256 = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &claimable_amount_msg)
+            .unwrap();
+
+        assert_eq!(claimable_amount, Uint256::from_u128(150_000_u128));
+
+        let nonce_used: bool = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &QueryMsg::IsVoucherNonceUsed { nonce: 1 })
+            .unwrap();
+
+        assert!(nonce_used);
+
+        let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &msg, &[]);
+
+        let error_message = "Voucher nonce already used".to_string();
+        expect_error(res, error_message);
+
+        let tampered = Voucher {
+            nonce: 2,
+            ..voucher.clone()
+        };
+
+        let msg = ExecuteMsg::RedeemVoucher {
+            voucher: Voucher {
+                amount: Uint256::from_u128(2_000_000_u128),
+                ..tampered.clone()
+            },
+            signature: sign_voucher(&signing_key, &tampered),
+        };
+
+        let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &msg, &[]);
+
+        let error_message = "Invalid voucher signature".to_string();
+        expect_error(res, error_message);
+
+        let expired = Voucher {
+            nonce: 3,
+            expires: app.block_info().time,
+            ..voucher
+        };
+
+        let msg = ExecuteMsg::RedeemVoucher {
+            voucher: expired.clone(),
+            signature: sign_voucher(&signing_key, &expired),
+        };
+
+        let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &msg, &[]);
+
+        let error_message = "Voucher expired".to_string();
+        expect_error(res, error_message);
+
+        // a second voucher must not replace the grant of the first one
+        let second = Voucher {
+            amount: Uint256::from_u128(500_u128),
+            nonce: 4,
+            ..tampered
+        };
+
+        let msg = ExecuteMsg::RedeemVoucher {
+            voucher: second.clone(),
+            signature: sign_voucher(&signing_key, &second),
+        };
+
+        let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &msg, &[]);
+
+        expect_error(res, "User already has a vesting position".to_string());
+
+        let claimable_amount: Uint256 = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &claimable_amount_msg)
+            .unwrap();
+
+        assert_eq!(claimable_amount, Uint256::from_u128(150_000_u128));
+    }
+
+    #[test]
+    fn claim_tokenized_grant() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let nft_code_id = app.store_code(grant_nft_template());
+
+        let nft_contract = app
+            .instantiate_contract(
+                nft_code_id,
+                Addr::unchecked(ADMIN),
+                &(cw721_base::InstantiateMsg {
+                    name: "Injex Vesting Grants".to_string(),
+                    symbol: "INJXG".to_string(),
+                    minter: contract.addr().to_string(),
+                }),
+                &[],
+                "grants",
+                None
+            )
+            .unwrap();
+
+        let msg = ExecuteMsg::SetGrantNft { nft_contract: nft_contract.to_string() };
+
+        let res = app.execute_contract(Addr::unchecked(ADMIN), contract.addr(), &msg, &[]);
+
+        assert!(res.is_ok());
+
+        let amount = Uint128::new(1_000_000);
+
+        let msg = ExecuteMsg::VestTokens {
+            users: vec![UserVesting {
+                amount: Uint256::from_uint128(amount),
+                user: USER.to_string(),
+                extra: vec![],
+            }],
+        };
+
+        let res = app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract.addr(),
+            &msg,
+            &[Coin {
+                denom: INJEX_TOKEN.to_string(),
+                amount,
+            }]
+        );
+
+        assert!(res.is_ok());
+
+        let grant: GrantResponse = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &QueryMsg::GetGrant { grant_id: 1 })
+            .unwrap();
+
+        assert_eq!(grant.user, Addr::unchecked(USER));
+
+        let owner: OwnerOfResponse = app
+            .wrap()
+            .query_wasm_smart(
+                nft_contract.clone(),
+                &(cw721_base::QueryMsg::<Empty>::OwnerOf {
+                    token_id: "1".to_string(),
+                    include_expired: None,
+                })
+            )
+            .unwrap();
+
+        assert_eq!(owner.owner, USER.to_string());
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            contract.addr(),
+            &ExecuteMsg::Claim {},
+            &[]
+        );
+
+        let error_message = "Grant is tokenized, claim it with the grant NFT".to_string();
+        expect_error(res, error_message);
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER),
+            nft_contract,
+            &(cw721_base::ExecuteMsg::<Option<Empty>, Empty>::TransferNft {
+                recipient: ADMIN.to_string(),
+                token_id: "1".to_string(),
+            }),
+            &[]
+        );
+
+        assert!(res.is_ok());
+
+        let msg = ExecuteMsg::ClaimGrant { grant_id: 1 };
+
+        let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &msg, &[]);
+
+        let error_message = "Only grant NFT owner".to_string();
+        expect_error(res, error_message);
+
+        let balance = app.wrap().query_balance(ADMIN.to_string(), INJEX_TOKEN.to_string()).unwrap();
+
+        let res = app.execute_contract(Addr::unchecked(ADMIN), contract.addr(), &msg, &[]);
+
+        assert!(res.is_ok());
+
+        let balance_after = app
+            .wrap()
+            .query_balance(ADMIN.to_string(), INJEX_TOKEN.to_string())
+            .unwrap();
+
+        let instant_amount =
+            (Uint256::from_uint128(amount) * Uint256::from_u128(1500_u128)) / PERCENTS;
+
+        assert_eq!(
+            Uint256::from_uint128(balance_after.amount),
+            Uint256::from_uint128(balance.amount) + instant_amount
+        );
+    }
+
+    #[test]
+    fn typed_client() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let amount = Uint128::new(1_000_000);
+
+        let msg = contract
+            .vest_tokens(
+                vec![UserVesting {
+                    amount: Uint256::from_uint128(amount),
+                    user: USER.to_string(),
+                    extra: vec![],
+                }],
+                vec![Coin {
+                    denom: INJEX_TOKEN.to_string(),
+                    amount,
+                }]
+            )
+            .unwrap();
+
+        let res = app.execute(Addr::unchecked(ADMIN), msg);
+
+        assert!(res.is_ok());
+
+        let instant_amount =
+            (Uint256::from_uint128(amount) * Uint256::from_u128(1500_u128)) / PERCENTS;
+
+        assert_eq!(contract.claimable_amount(&app.wrap(), USER).unwrap(), instant_amount);
+        assert_eq!(contract.instant_claim(&app.wrap(), USER).unwrap(), instant_amount);
+        assert_eq!(contract.total_vested(&app.wrap()).unwrap(), Uint256::from_uint128(amount));
+        assert_eq!(contract.injx_token(&app.wrap()).unwrap().denom, INJEX_TOKEN.to_string());
+        assert_eq!(contract.grant(&app.wrap(), 1).unwrap().user, Addr::unchecked(USER));
+
+        let res = app.execute(Addr::unchecked(USER), contract.claim().unwrap());
+
+        assert!(res.is_ok());
+
+        assert_eq!(contract.total_claimed(&app.wrap()).unwrap(), instant_amount);
+        assert_eq!(contract.claimable_amount(&app.wrap(), USER).unwrap(), Uint256::zero());
+
+        let lock_minutes = ConfigChange::LockMinutes {
+            new_lock_minutes: Uint256::from_u128(10_u128),
+        };
+        let effective_time = app.block_info().time.plus_seconds(3600);
+
+        let res = app.execute(
+            Addr::unchecked(ADMIN),
+            contract.schedule_config_change(lock_minutes, effective_time).unwrap()
+        );
+
+        assert!(res.is_ok());
+
+        app.update_block(|block| {
+            block.time = effective_time;
+        });
+
+        app.execute(Addr::unchecked(USER), contract.execute_config_change(1).unwrap()).unwrap();
+
+        assert_eq!(contract.config(&app.wrap()).unwrap().lock_minutes, Uint256::from_u128(10_u128));
+    }
+
+    fn event_attribute(res: &AppResponse, event_type: &str, key: &str) -> String {
+        res.events
+            .iter()
+            .find(|event| event.ty == event_type)
+            .and_then(|event| event.attributes.iter().find(|attr| attr.key == key))
+            .map(|attr| attr.value.clone())
+            .unwrap()
+    }
+
+    #[test]
+    fn structured_events() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let amount = Uint128::new(1_000_000);
+
+        let msg = ExecuteMsg::VestTokens {
+            users: vec![UserVesting {
+                amount: Uint256::from_uint128(amount),
+                user: USER.to_string(),
+                extra: vec![],
+            }],
+        };
+
+        let res = app
+            .execute_contract(
+                Addr::unchecked(ADMIN),
+                contract.addr(),
+                &msg,
+                &[Coin {
+                    denom: INJEX_TOKEN.to_string(),
+                    amount,
+                }]
+            )
+            .unwrap();
+
+        assert_eq!(event_attribute(&res, "wasm-vest_created", "beneficiary"), USER);
+        assert_eq!(event_attribute(&res, "wasm-vest_created", "grant_id"), "1");
+        assert_eq!(event_attribute(&res, "wasm-vest_created", "instant_amount"), "150000");
+        assert_eq!(event_attribute(&res, "wasm-vest_created", "periodic_amount"), "850000");
+        assert_eq!(event_attribute(&res, "wasm-vest_created", "amount_per_period"), "170000");
+        assert_eq!(event_attribute(&res, "wasm-vest_created", "lock_periods"), "5");
+
+        let res = app
+            .execute_contract(Addr::unchecked(USER), contract.addr(), &ExecuteMsg::Claim {}, &[])
+            .unwrap();
+
+        assert_eq!(event_attribute(&res, "wasm-claim", "beneficiary"), USER);
+        assert_eq!(event_attribute(&res, "wasm-claim", "instant_amount"), "150000");
+        assert_eq!(event_attribute(&res, "wasm-claim", "periodic_amount"), "0");
+
+        let msg = ExecuteMsg::ScheduleConfigChange {
+            change: ConfigChange::LockMinutes { new_lock_minutes: Uint256::from_u128(10_u128) },
+            effective_time: app.block_info().time.plus_seconds(3600),
+        };
+
+        app.execute_contract(Addr::unchecked(ADMIN), contract.addr(), &msg, &[]).unwrap();
+
+        app.update_block(|block| {
+            block.time = block.time.plus_seconds(3600);
+        });
+
+        let msg = ExecuteMsg::ExecuteConfigChange { id: 1 };
+
+        let res = app.execute_contract(Addr::unchecked(USER), contract.addr(), &msg, &[]).unwrap();
+
+        assert_eq!(event_attribute(&res, "wasm-config_changed", "key"), "lock_minutes");
+        assert_eq!(event_attribute(&res, "wasm-config_changed", "old_value"), "5");
+        assert_eq!(event_attribute(&res, "wasm-config_changed", "new_value"), "10");
+
+        let msg = ExecuteMsg::ChangeAdmin { new_admin: USER.to_string() };
+
+        let res = app.execute_contract(Addr::unchecked(ADMIN), contract.addr(), &msg, &[]).unwrap();
+
+        assert_eq!(event_attribute(&res, "wasm-admin_changed", "old_admin"), ADMIN);
+        assert_eq!(event_attribute(&res, "wasm-admin_changed", "new_admin"), USER);
+    }
+
+    #[test]
+    fn claim_history() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let amount = Uint128::new(1_000_000);
+
+        let msg = contract
+            .vest_tokens(
+                vec![UserVesting {
+                    amount: Uint256::from_uint128(amount),
+                    user: USER.to_string(),
+                    extra: vec![],
+                }],
+                vec![Coin {
+                    denom: INJEX_TOKEN.to_string(),
+                    amount,
+                }]
+            )
+            .unwrap();
+
+        app.execute(Addr::unchecked(ADMIN), msg).unwrap();
+        app.execute(Addr::unchecked(USER), contract.claim().unwrap()).unwrap();
+
+        for _ in 0..2 {
+            app.update_block(|block| {
+                block.height += 1;
+                block.time = block.time.plus_minutes(5);
+            });
+
+            app.execute(Addr::unchecked(USER), contract.claim().unwrap()).unwrap();
+        }
+
+        let history = contract.claim_history(&app.wrap(), USER, None, None).unwrap();
+
+        assert_eq!(history.claims.len(), 3);
+        assert_eq!(history.claims[0].id, 0);
+        assert_eq!(history.claims[0].instant_amount, Uint256::from_u128(150_000_u128));
+        assert_eq!(history.claims[0].periodic_amount, Uint256::zero());
+        assert_eq!(history.claims[1].instant_amount, Uint256::zero());
+        assert_eq!(history.claims[1].periodic_amount, Uint256::from_u128(170_000_u128));
+        assert_eq!(history.claims[2].height, history.claims[1].height + 1);
+        assert_eq!(history.claims[2].recipient, Addr::unchecked(USER));
+
+        let page = contract.claim_history(&app.wrap(), USER, Some(0), Some(1)).unwrap();
+
+        assert_eq!(page.claims.len(), 1);
+        assert_eq!(page.claims[0], history.claims[1]);
+    }
+
+    #[test]
+    fn simulate_at() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let amount = Uint128::new(1_000_000);
+
+        let msg = contract
+            .vest_tokens(
+                vec![UserVesting {
+                    amount: Uint256::from_uint128(amount),
+                    user: USER.to_string(),
+                    extra: vec![],
+                }],
+                vec![Coin {
+                    denom: INJEX_TOKEN.to_string(),
+                    amount,
+                }]
+            )
+            .unwrap();
+
+        app.execute(Addr::unchecked(ADMIN), msg).unwrap();
+
+        let start = app.block_info().time;
+
+        let simulation = contract.simulate_at(&app.wrap(), USER, start.plus_minutes(12)).unwrap();
+
+        assert_eq!(simulation.instant_claimable, Uint256::from_u128(150_000_u128));
+        assert_eq!(simulation.periodic_claimable, Uint256::from_u128(340_000_u128));
+        assert_eq!(simulation.claimable, Uint256::from_u128(490_000_u128));
+        assert_eq!(simulation.vested, Uint256::from_u128(490_000_u128));
+        assert_eq!(simulation.locked, Uint256::from_u128(510_000_u128));
+        assert_eq!(simulation.next_unlock, Some(start.plus_minutes(15)));
+
+        app.update_block(|block| {
+            block.time = block.time.plus_minutes(12);
+        });
+
+        let balance = app.wrap().query_balance(USER.to_string(), INJEX_TOKEN.to_string()).unwrap();
+
+        app.execute(Addr::unchecked(USER), contract.claim().unwrap()).unwrap();
+
+        let balance_after = app
+            .wrap()
+            .query_balance(USER.to_string(), INJEX_TOKEN.to_string())
+            .unwrap();
+
+        assert_eq!(
+            Uint256::from_uint128(balance_after.amount - balance.amount),
+            simulation.claimable
+        );
+
+        let simulation = contract.simulate_at(&app.wrap(), USER, start.plus_minutes(60)).unwrap();
+
+        assert_eq!(simulation.instant_claimable, Uint256::zero());
+        assert_eq!(simulation.periodic_claimable, Uint256::from_u128(510_000_u128));
+        assert_eq!(simulation.vested, Uint256::from_u128(1_000_000_u128));
+        assert_eq!(simulation.locked, Uint256::zero());
+        assert_eq!(simulation.next_unlock, None);
+    }
+
+    #[test]
+    fn unlock_schedule() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let amount = Uint128::new(1_000_000);
+
+        let msg = contract
+            .vest_tokens(
+                vec![UserVesting {
+                    amount: Uint256::from_uint128(amount),
+                    user: USER.to_string(),
+                    extra: vec![],
+                }],
+                vec![Coin {
+                    denom: INJEX_TOKEN.to_string(),
+                    amount,
+                }]
+            )
+            .unwrap();
+
+        app.execute(Addr::unchecked(ADMIN), msg).unwrap();
+
+        let start = app.block_info().time;
+
+        app.update_block(|block| {
+            block.time = block.time.plus_minutes(7);
+        });
+
+        app.execute(Addr::unchecked(USER), contract.claim().unwrap()).unwrap();
+
+        let schedule = contract.unlock_schedule(&app.wrap(), USER).unwrap();
+
+        assert_eq!(schedule.tranches.len(), 6);
+
+        assert_eq!(schedule.tranches[0], UnlockTranche {
+            timestamp: start,
+            amount: Uint256::from_u128(150_000_u128),
+            cumulative: Uint256::from_u128(150_000_u128),
+            instant: true,
+            claimed: true,
+        });
+        assert_eq!(schedule.tranches[1], UnlockTranche {
+            timestamp: start.plus_minutes(5),
+            amount: Uint256::from_u128(170_000_u128),
+            cumulative: Uint256::from_u128(320_000_u128),
+            instant: false,
+            claimed: true,
+        });
+        assert_eq!(schedule.tranches[5], UnlockTranche {
+            timestamp: start.plus_minutes(25),
+            amount: Uint256::from_u128(170_000_u128),
+            cumulative: Uint256::from_u128(1_000_000_u128),
+            instant: false,
+            claimed: false,
+        });
+        assert!(!schedule.tranches[2].claimed);
+    }
+
+    #[test]
+    fn stats() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let amount = Uint128::new(1_000_000);
+
+        let msg = contract
+            .vest_tokens(
+                vec![
+                    UserVesting {
+                        amount: Uint256::from_uint128(amount),
+                        user: USER.to_string(),
+                        extra: vec![],
+                    },
+                    UserVesting {
+                        amount: Uint256::from_uint128(amount),
+                        user: ADMIN.to_string(),
+                        extra: vec![],
+                    }
+                ],
+                vec![Coin {
+                    denom: INJEX_TOKEN.to_string(),
+                    amount: amount + amount,
+                }]
+            )
+            .unwrap();
+
+        app.execute(Addr::unchecked(ADMIN), msg).unwrap();
+
+        app.update_block(|block| {
+            block.time = block.time.plus_minutes(7);
+        });
+
+        app.execute(Addr::unchecked(USER), contract.claim().unwrap()).unwrap();
+
+        let stats = contract.stats(&app.wrap()).unwrap();
+
+        let balance = app
+            .wrap()
+            .query_balance(contract.addr().to_string(), INJEX_TOKEN.to_string())
+            .unwrap();
+
+        assert_eq!(stats.beneficiary_count, 2);
+        assert_eq!(stats.fully_claimed_count, 0);
+        assert_eq!(stats.total_vested, Uint256::from_u128(2_000_000_u128));
+        assert_eq!(stats.total_claimed, Uint256::from_u128(320_000_u128));
+        assert_eq!(stats.instant_liability, Uint256::from_u128(150_000_u128));
+        assert_eq!(stats.unlocked_unclaimed, Uint256::from_u128(170_000_u128));
+        assert_eq!(stats.locked, Uint256::from_u128(1_360_000_u128));
+        assert_eq!(stats.balance, Uint256::from_uint128(balance.amount));
+        assert_eq!(
+            stats.surplus,
+            Uint256::from_uint128(balance.amount) - Uint256::from_u128(1_680_000_u128)
+        );
+        assert_eq!(stats.deficit, Uint256::zero());
+
+        app.update_block(|block| {
+            block.time = block.time.plus_minutes(30);
+        });
+
+        app.execute(Addr::unchecked(USER), contract.claim().unwrap()).unwrap();
+
+        let stats = contract.stats(&app.wrap()).unwrap();
+
+        assert_eq!(stats.fully_claimed_count, 1);
+        assert_eq!(stats.locked, Uint256::zero());
+        assert_eq!(stats.unlocked_unclaimed, Uint256::from_u128(850_000_u128));
+    }
+
+    #[test]
+    fn check_solvency_and_audit_positions() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let amount = Uint128::new(1_000_000);
+
+        let msg = contract
+            .vest_tokens(
+                vec![
+                    UserVesting {
+                        amount: Uint256::from_uint128(amount),
+                        user: USER.to_string(),
+                        extra: vec![],
+                    },
+                    UserVesting {
+                        amount: Uint256::from_uint128(amount),
+                        user: ADMIN.to_string(),
+                        extra: vec![],
+                    }
+                ],
+                vec![Coin {
+                    denom: INJEX_TOKEN.to_string(),
+                    amount: amount + amount,
+                }]
+            )
+            .unwrap();
+
+        app.execute(Addr::unchecked(ADMIN), msg).unwrap();
+
+        app.update_block(|block| {
+            block.time = block.time.plus_minutes(7);
+        });
+
+        app.execute(Addr::unchecked(USER), contract.claim().unwrap()).unwrap();
+
+        let balance = app
+            .wrap()
+            .query_balance(contract.addr().to_string(), INJEX_TOKEN.to_string())
+            .unwrap();
+
+        let solvency = contract.check_solvency(&app.wrap()).unwrap();
+
+        assert_eq!(solvency.balance, Uint256::from_uint128(balance.amount));
+        assert_eq!(solvency.outstanding, Uint256::from_u128(1_680_000_u128));
+        assert_eq!(solvency.unregistered_merkle, Uint256::zero());
+        assert!(solvency.solvent);
+        assert_eq!(solvency.shortfall, Uint256::zero());
+
+        let audit = contract.audit_positions(&app.wrap(), None, Some(1)).unwrap();
+
+        assert_eq!(audit.positions.len(), 1);
+        assert_eq!(audit.positions[0].user, Addr::unchecked(USER));
+        assert_eq!(audit.positions[0].granted, Uint256::from_u128(1_000_000_u128));
+        assert_eq!(audit.positions[0].claimed, Uint256::from_u128(320_000_u128));
+        assert_eq!(audit.positions[0].liability, Uint256::from_u128(680_000_u128));
+        assert!(audit.positions[0].issues.is_empty());
+
+        let audit = contract
+            .audit_positions(&app.wrap(), Some(USER.to_string()), None)
+            .unwrap();
+
+        assert_eq!(audit.positions.len(), 1);
+        assert_eq!(audit.positions[0].user, Addr::unchecked(ADMIN));
+        assert_eq!(audit.positions[0].granted, Uint256::from_u128(1_000_000_u128));
+        assert_eq!(audit.positions[0].claimed, Uint256::zero());
+        assert_eq!(audit.positions[0].liability, Uint256::from_u128(1_000_000_u128));
+        assert!(audit.positions[0].issues.is_empty());
+    }
+
+    #[test]
+    fn multi_denom_vesting() {
+        let (mut app, contract) = proper_instantiate(false);
+
+        let amount = Uint128::new(1_000_000);
+        let usdt_amount = Uint128::new(200_000);
+
+        let users = vec![UserVesting {
+            amount: Uint256::from_uint128(amount),
+            user: USER.to_string(),
+            extra: vec![Coin {
+                denom: USDT.to_string(),
+                amount: usdt_amount,
+            }],
+        }];
+        let funds = vec![
+            Coin {
+                denom: INJEX_TOKEN.to_string(),
+                amount,
+            },
+            Coin {
+                denom: USDT.to_string(),
+                amount: usdt_amount,
+            }
+        ];
+
+        let res = app.execute(
+            Addr::unchecked(ADMIN),
+            contract.vest_tokens(users.clone(), funds.clone()).unwrap()
+        );
+        expect_error(res, "Invalid coin passed in funds".to_string());
+
+        let res = app.execute(
+            Addr::unchecked(ADMIN),
+            contract.set_extra_denoms(vec![INJEX_TOKEN.to_string()]).unwrap()
+        );
+        expect_error(res, "Invalid extra denoms".to_string());
+
+        app.execute(
+            Addr::unchecked(ADMIN),
+            contract.set_extra_denoms(vec![USDT.to_string()]).unwrap()
+        ).unwrap();
+
+        let res = app.execute(
+            Addr::unchecked(ADMIN),
+            contract.vest_tokens(users.clone(), funds[..1].to_vec()).unwrap()
+        );
+        expect_error(res, "Insufficient unallocated funds".to_string());
+
+        app.execute(Addr::unchecked(ADMIN), contract.vest_tokens(users, funds).unwrap()).unwrap();
+
+        app.update_block(|block| {
+            block.time = block.time.plus_minutes(7);
+        });
+
+        // 15% instant and one of five periods
+        assert_eq!(
+            contract.extra_claimable(&app.wrap(), USER).unwrap(),
+            vec![Coin {
+                denom: USDT.to_string(),
+                amount: Uint128::new(64_000),
+            }]
+        );
+
+        let usdt_before = app.wrap().query_balance(USER, USDT).unwrap().amount;
+
+        app.execute(Addr::unchecked(USER), contract.claim().unwrap()).unwrap();
+
+        assert_eq!(
+            app.wrap().query_balance(USER, USDT).unwrap().amount,
+            usdt_before + Uint128::new(64_000)
+        );
+        assert_eq!(
+            app.wrap().query_balance(contract.addr(), INJEX_TOKEN).unwrap().amount,
+            Uint128::new(680_000)
+        );
+        assert_eq!(contract.extra_claimable(&app.wrap(), USER).unwrap()[0].amount, Uint128::zero());
+
+        let totals = contract.totals(&app.wrap()).unwrap();
+
+        assert_eq!(totals.extra.len(), 1);
+        assert_eq!(totals.extra[0].denom, USDT.to_string());
+        assert_eq!(totals.extra[0].total_vested, Uint256::from_uint128(usdt_amount));
+        assert_eq!(totals.extra[0].total_claimed, Uint256::from_u128(64_000_u128));
+
+        app.update_block(|block| {
+            block.time = block.time.plus_minutes(30);
+        });
+
+        app.execute(Addr::unchecked(USER), contract.claim().unwrap()).unwrap();
+
+        assert_eq!(
+            app.wrap().query_balance(USER, USDT).unwrap().amount,
+            usdt_before + usdt_amount
+        );
+        assert_eq!(
+            app.wrap().query_balance(contract.addr(), USDT).unwrap().amount,
+            Uint128::zero()
+        );
+    }
+
+    #[test]
+    fn factory_instances_and_positions() {
+        let mut app = mock_app();
+        let vesting_code_id = app.store_code(contract_template());
+        let factory_code_id = app.store_code(factory_template());
+
+        let factory_addr = app
+            .instantiate_contract(
+                factory_code_id,
+                Addr::unchecked(ADMIN),
+                &(crate::factory::msg::InstantiateMsg {
+                    admin: ADMIN.to_string(),
+                    vesting_code_id,
+                }),
+                &[],
+                "factory",
+                None
+            )
+            .unwrap();
+        let factory = FactoryContract(factory_addr);
+
+        let vesting_msg = InstantiateMsg {
+            instant_claim_percents: Uint256::from_u128(1500_u128),
+            lock_minutes: Uint256::from_u128(5_u128),
+            lock_periods: Uint256::from_u128(5_u128),
+            injex_token: INJEX_TOKEN.to_string(),
+            admin: ADMIN.to_string(),
+            voucher_signer: None,
+            extra_denoms: vec![],
+            max_total_allocation: None,
+            treasury: None,
+            mint_cap: None,
+            early_unlock: None,
+            reward_denoms: vec![],
+            min_delay: 3600,
+            approvals: None,
+            dao: None,
+        };
+
+        let res = app.execute(
+            Addr::unchecked(USER),
+            factory.create_instance("seed", vesting_msg.clone(), vec![]).unwrap()
+        );
+        expect_error(res, "Only admin".to_string());
+
+        for label in ["seed", "private"] {
+            app.execute(
+                Addr::unchecked(ADMIN),
+                factory.create_instance(label, vesting_msg.clone(), vec![]).unwrap()
+            ).unwrap();
+        }
+
+        assert_eq!(factory.config(&app.wrap()).unwrap().instance_count, 2);
+
+        let instances = factory.instances(&app.wrap(), None, None).unwrap().instances;
+
+        assert_eq!(instances.len(), 2);
+        assert_eq!(instances[0].id, 1);
+        assert_eq!(instances[0].label, "seed".to_string());
+        assert_eq!(instances[0].owner, Addr::unchecked(ADMIN));
+        assert_eq!(instances[1].label, "private".to_string());
+        assert_eq!(factory.instance(&app.wrap(), 2).unwrap(), instances[1]);
+        assert_eq!(
+            factory.instance_by_address(&app.wrap(), instances[0].address.clone()).unwrap(),
+            instances[0]
+        );
+        assert!(factory.instance_by_address(&app.wrap(), USER).is_err());
+        assert_eq!(
+            factory.instances(&app.wrap(), Some(1), None).unwrap().instances,
+            vec![instances[1].clone()]
+        );
+
+        // instance admin is the factory admin for migrations
+        assert_eq!(
+            app.wrap().query_wasm_contract_info(instances[0].address.clone()).unwrap().admin,
+            Some(ADMIN.to_string())
+        );
+
+        let amount = Uint128::new(1_000_000);
+
+        for instance in &instances {
+            let vesting = VestingContract(instance.address.clone());
+
+            let msg = vesting
+                .vest_tokens(
+                    vec![UserVesting {
+                        amount: Uint256::from_uint128(amount),
+                        user: USER.to_string(),
+                        extra: vec![],
+                    }],
+                    vec![Coin {
+                        denom: INJEX_TOKEN.to_string(),
+                        amount,
+                    }]
+                )
+                .unwrap();
+
+            app.execute(Addr::unchecked(ADMIN), msg).unwrap();
+        }
+
+        app.update_block(|block| {
+            block.time = block.time.plus_minutes(7);
+        });
+
+        let positions = factory.user_positions(&app.wrap(), USER, None, None).unwrap();
+
+        assert_eq!(positions.positions.len(), 2);
+        assert_eq!(positions.positions[0].label, "seed".to_string());
+        assert_eq!(positions.positions[0].claimable, Uint256::from_u128(320_000_u128));
+        assert_eq!(positions.totals.len(), 1);
+        assert_eq!(positions.totals[0].denom, INJEX_TOKEN.to_string());
+        assert_eq!(positions.totals[0].claimable, Uint256::from_u128(640_000_u128));
+        assert_eq!(positions.totals[0].locked, Uint256::from_u128(1_360_000_u128));
+
+        let positions = factory.user_positions(&app.wrap(), ADMIN, None, None).unwrap();
+
+        assert!(positions.positions.is_empty());
+    }
+
+    #[test]
+    fn deposit_pool_and_allocation_cap() {
+        let (mut app, contract) = proper_instantiate(false);
+
+        let deposit = Uint128::new(1_500_000);
+
+        let res = app.execute(
+            Addr::unchecked(USER),
+            contract
+                .deposit(
+                    vec![Coin {
+                        denom: INJEX_TOKEN.to_string(),
+                        amount: deposit,
+                    }]
+                )
+                .unwrap()
+        );
+        expect_error(res, "Only admin".to_string());
+
+        app.execute(
+            Addr::unchecked(ADMIN),
+            contract
+                .deposit(
+                    vec![Coin {
+                        denom: INJEX_TOKEN.to_string(),
+                        amount: deposit,
+                    }]
+                )
+                .unwrap()
+        ).unwrap();
+
+        app.execute(
+            Addr::unchecked(ADMIN),
+            contract.set_max_total_allocation(Some(Uint256::from_u128(1_200_000_u128))).unwrap()
+        ).unwrap();
+
+        let vest = |amount: u128| {
+            contract
+                .vest_tokens(
+                    vec![UserVesting {
+                        amount: Uint256::from_u128(amount),
+                        user: USER.to_string(),
+                        extra: vec![],
+                    }],
+                    vec![]
+                )
+                .unwrap()
+        };
+
+        app.execute(Addr::unchecked(ADMIN), vest(1_000_000)).unwrap();
+
+        let unallocated = contract.unallocated(&app.wrap()).unwrap();
+
+        assert_eq!(
+            unallocated.unallocated,
+            vec![Coin {
+                denom: INJEX_TOKEN.to_string(),
+                amount: Uint128::new(500_000),
+            }]
+        );
+        assert_eq!(unallocated.total_allocated, Uint256::from_u128(1_000_000_u128));
+        assert_eq!(unallocated.remaining_allocation, Some(Uint256::from_u128(200_000_u128)));
+
+        let res = app.execute(Addr::unchecked(ADMIN), vest(300_000));
+        expect_error(res, "Max total allocation exceeded".to_string());
+
+        let res = app.execute(
+            Addr::unchecked(ADMIN),
+            contract.set_max_total_allocation(Some(Uint256::from_u128(900_000_u128))).unwrap()
+        );
+        expect_error(res, "Max total allocation exceeded".to_string());
+
+        app.execute(
+            Addr::unchecked(ADMIN),
+            contract.set_max_total_allocation(None).unwrap()
+        ).unwrap();
+
+        let res = app.execute(Addr::unchecked(ADMIN), vest(600_000));
+        expect_error(res, "Insufficient unallocated funds".to_string());
+
+        // claims do not change what is left to allocate
+        app.update_block(|block| {
+            block.time = block.time.plus_minutes(7);
+        });
+
+        app.execute(Addr::unchecked(USER), contract.claim().unwrap()).unwrap();
+
+        assert_eq!(
+            contract.unallocated(&app.wrap()).unwrap().unallocated[0].amount,
+            Uint128::new(500_000)
+        );
+    }
+
+    #[test]
+    fn amend_grant() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let msg = contract
+            .vest_tokens(
+                vec![UserVesting {
+                    amount: Uint256::from_u128(1_000_000_u128),
+                    user: USER.to_string(),
+                    extra: vec![],
+                }],
+                vec![]
+            )
+            .unwrap();
+
+        app.execute(Addr::unchecked(ADMIN), msg).unwrap();
+
+        let treasury = "treasury".to_string();
+
+        app.execute(
+            Addr::unchecked(ADMIN),
+            contract.change_treasury(Some(treasury.clone())).unwrap()
+        ).unwrap();
+
+        app.update_block(|block| {
+            block.time = block.time.plus_minutes(7);
+        });
+
+        let res = app.execute(
+            Addr::unchecked(ADMIN),
+            contract.increase_grant(USER, 2, Uint256::from_u128(100_000_u128), vec![]).unwrap()
+        );
+        expect_error(res, "Grant not found".to_string());
+
+        // the remaining four periods go from 170_000 to 195_000
+        app.execute(
+            Addr::unchecked(ADMIN),
+            contract.increase_grant(USER, 1, Uint256::from_u128(100_000_u128), vec![]).unwrap()
+        ).unwrap();
+
+        assert_eq!(
+            contract.claimable_amount(&app.wrap(), USER).unwrap(),
+            Uint256::from_u128(320_000_u128)
+        );
+
+        app.execute(Addr::unchecked(USER), contract.claim().unwrap()).unwrap();
+
+        app.update_block(|block| {
+            block.time = block.time.plus_minutes(5);
+        });
+
+        assert_eq!(
+            contract.claimable_amount(&app.wrap(), USER).unwrap(),
+            Uint256::from_u128(195_000_u128)
+        );
+
+        let res = app.execute(
+            Addr::unchecked(ADMIN),
+            contract.decrease_grant(USER, 1, Uint256::from_u128(585_001_u128)).unwrap()
+        );
+        expect_error(res, "Decrease exceeds locked amount".to_string());
+
+        // 484_999 left over three periods, the remainder is refunded as well
+        app.execute(
+            Addr::unchecked(ADMIN),
+            contract.decrease_grant(USER, 1, Uint256::from_u128(100_001_u128)).unwrap()
+        ).unwrap();
+
+        assert_eq!(
+            app.wrap().query_balance(treasury, INJEX_TOKEN).unwrap().amount,
+            Uint128::new(100_002)
+        );
+
+        let stats = contract.stats(&app.wrap()).unwrap();
+
+        assert_eq!(stats.total_vested, Uint256::from_u128(999_998_u128));
+        assert_eq!(stats.unlocked_unclaimed, Uint256::from_u128(195_000_u128));
+        assert_eq!(stats.locked, Uint256::from_u128(484_998_u128));
+        assert_eq!(
+            contract.claimable_amount(&app.wrap(), USER).unwrap(),
+            Uint256::from_u128(195_000_u128)
+        );
+
+        let schedule = contract.unlock_schedule(&app.wrap(), USER).unwrap().tranches;
+
+        assert_eq!(schedule.len(), 5);
+        assert_eq!(schedule[1].amount, Uint256::from_u128(365_000_u128));
+        assert_eq!(schedule[4].amount, Uint256::from_u128(161_666_u128));
+        assert_eq!(schedule[4].cumulative, Uint256::from_u128(999_998_u128));
+
+        app.execute(Addr::unchecked(USER), contract.claim().unwrap()).unwrap();
+
+        app.update_block(|block| {
+            block.time = block.time.plus_minutes(30);
+        });
+
+        app.execute(Addr::unchecked(USER), contract.claim().unwrap()).unwrap();
+
+        let stats = contract.stats(&app.wrap()).unwrap();
+
+        assert_eq!(stats.total_claimed, Uint256::from_u128(999_998_u128));
+        assert_eq!(stats.fully_claimed_count, 1);
+        assert_eq!(stats.locked, Uint256::zero());
+        assert_eq!(stats.unlocked_unclaimed, Uint256::zero());
+
+        let res = app.execute(
+            Addr::unchecked(ADMIN),
+            contract.increase_grant(USER, 1, Uint256::from_u128(100_000_u128), vec![]).unwrap()
+        );
+        expect_error(res, "Grant is fully vested".to_string());
+    }
+
+    #[test]
+    fn sudo_overrides() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let msg = contract
+            .vest_tokens(
+                vec![UserVesting {
+                    amount: Uint256::from_u128(1_000_000_u128),
+                    user: USER.to_string(),
+                    extra: vec![],
+                }],
+                vec![]
+            )
+            .unwrap();
+
+        app.execute(Addr::unchecked(ADMIN), msg).unwrap();
+
+        app.wasm_sudo(contract.addr(), &(SudoMsg::SetPaused { paused: true })).unwrap();
+
+        assert!(contract.is_paused(&app.wrap()).unwrap());
+
+        let res = app.execute(Addr::unchecked(USER), contract.claim().unwrap());
+        expect_error(res, "Contract is paused".to_string());
+
+        app.wasm_sudo(contract.addr(), &(SudoMsg::SetPaused { paused: false })).unwrap();
+
+        app.wasm_sudo(
+            contract.addr(),
+            &(SudoMsg::ReplaceAdmin { new_admin: USER.to_string() })
+        ).unwrap();
+
+        assert_eq!(contract.config(&app.wrap()).unwrap().admin, Addr::unchecked(USER));
+
+        app.update_block(|block| {
+            block.time = block.time.plus_minutes(7);
+        });
+
+        // unlocked tokens stay with the user, the four locked periods are revoked
+        app.wasm_sudo(
+            contract.addr(),
+            &(SudoMsg::RevokeGrant { user: USER.to_string(), redistribute: false })
+        ).unwrap();
+
+        assert_eq!(
+            contract.claimable_amount(&app.wrap(), USER).unwrap(),
+            Uint256::from_u128(320_000_u128)
+        );
+        assert_eq!(contract.total_vested(&app.wrap()).unwrap(), Uint256::from_u128(320_000_u128));
+
+        app.execute(Addr::unchecked(USER), contract.claim().unwrap()).unwrap();
+
+        app.update_block(|block| {
+            block.time = block.time.plus_minutes(30);
+        });
+
+        let res = app.execute(Addr::unchecked(USER), contract.claim().unwrap());
+        expect_error(res, "Cannot claim".to_string());
+
+        assert_eq!(contract.stats(&app.wrap()).unwrap().fully_claimed_count, 1);
+    }
+
+    #[test]
+    fn mint_on_claim() {
+        let mut app = mock_app();
+        let code_id = app.store_code(contract_template());
+
+        let contract_addr = app
+            .instantiate_contract(
+                code_id,
+                Addr::unchecked(ADMIN),
+                &(InstantiateMsg {
+                    instant_claim_percents: Uint256::from_u128(1500_u128), // 15%
+                    lock_minutes: Uint256::from_u128(5_u128),
+                    lock_periods: Uint256::from_u128(5_u128),
+                    injex_token: INJEX_TOKEN.to_string(),
+                    admin: ADMIN.to_string(),
+                    voucher_signer: None,
+                    extra_denoms: vec![],
+                    max_total_allocation: None,
+                    treasury: None,
+                    mint_cap: Some(Uint256::from_u128(1_200_000_u128)),
+                    early_unlock: None,
+                    reward_denoms: vec![],
+                    min_delay: 3600,
+                    approvals: None,
+                    dao: None,
+                }),
+                &[],
+                "test",
+                None
+            )
+            .unwrap();
+        let contract = VestingContract(contract_addr);
+
+        let vest = |amount: u128, funds: Vec<Coin>| {
+            contract
+                .vest_tokens(
+                    vec![UserVesting {
+                        amount: Uint256::from_u128(amount),
+                        user: USER.to_string(),
+                        extra: vec![],
+                    }],
+                    funds
+                )
+                .unwrap()
+        };
+
+        let res = app.execute(
+            Addr::unchecked(ADMIN),
+            vest(
+                1_000_000,
+                vec![Coin {
+                    denom: INJEX_TOKEN.to_string(),
+                    amount: Uint128::new(1_000_000),
+                }]
+            )
+        );
+        expect_error(res, "Invalid funds were provided".to_string());
+
+        app.execute(Addr::unchecked(ADMIN), vest(1_000_000, vec![])).unwrap();
+
+        let res = app.execute(Addr::unchecked(ADMIN), vest(300_000, vec![]));
+        expect_error(res, "Mint cap exceeded".to_string());
+
+        let res = app.execute(
+            Addr::unchecked(ADMIN),
+            contract
+                .deposit(
+                    vec![Coin {
+                        denom: INJEX_TOKEN.to_string(),
+                        amount: Uint128::new(100),
+                    }]
+                )
+                .unwrap()
+        );
+        expect_error(res, "Not supported in mint mode".to_string());
+
+        assert_eq!(
+            contract.unallocated(&app.wrap()).unwrap().remaining_allocation,
+            Some(Uint256::from_u128(200_000_u128))
+        );
+
+        // the cap backs the grants instead of a balance
+        let solvency = contract.check_solvency(&app.wrap()).unwrap();
+        assert_eq!(solvency.balance, Uint256::zero());
+        assert_eq!(solvency.mintable, Uint256::from_u128(1_200_000_u128));
+        assert!(solvency.solvent);
+        assert_eq!(solvency.shortfall, Uint256::zero());
+
+        let stats = contract.stats(&app.wrap()).unwrap();
+        assert_eq!(stats.surplus, Uint256::from_u128(200_000_u128));
+        assert_eq!(stats.deficit, Uint256::zero());
+
+        let balance_before = app.wrap().query_balance(USER, INJEX_TOKEN).unwrap().amount;
+
+        app.update_block(|block| {
+            block.time = block.time.plus_minutes(7);
+        });
+
+        app.execute(Addr::unchecked(USER), contract.claim().unwrap()).unwrap();
+
+        // instant part and the first period are minted straight to the beneficiary
+        assert_eq!(
+            app.wrap().query_balance(USER, INJEX_TOKEN).unwrap().amount - balance_before,
+            Uint128::new(320_000)
+        );
+        assert_eq!(
+            app.wrap().query_balance(contract.addr(), INJEX_TOKEN).unwrap().amount,
+            Uint128::zero()
+        );
+        assert_eq!(contract.total_claimed(&app.wrap()).unwrap(), Uint256::from_u128(320_000_u128));
+
+        let solvency = contract.check_solvency(&app.wrap()).unwrap();
+        assert_eq!(solvency.mintable, Uint256::from_u128(880_000_u128));
+        assert_eq!(solvency.outstanding, Uint256::from_u128(680_000_u128));
+        assert!(solvency.solvent);
+
+        // merkle roots are obligations under the same cap
+        let vesting_start = app.block_info().time;
+        let merkle_root = hex::encode(merkle_leaf(ADMIN, 200_000, vesting_start));
+
+        let res = app.execute(
+            Addr::unchecked(ADMIN),
+            contract
+                .register_merkle_root(
+                    merkle_root.clone(),
+                    Uint256::from_u128(200_000_u128),
+                    vec![Coin {
+                        denom: INJEX_TOKEN.to_string(),
+                        amount: Uint128::new(200_000),
+                    }]
+                )
+                .unwrap()
+        );
+        expect_error(res, "Invalid funds were provided".to_string());
+
+        let res = app.execute(
+            Addr::unchecked(ADMIN),
+            contract
+                .register_merkle_root(merkle_root.clone(), Uint256::from_u128(300_000_u128), vec![])
+                .unwrap()
+        );
+        expect_error(res, "Mint cap exceeded".to_string());
+
+        app.execute(
+            Addr::unchecked(ADMIN),
+            contract
+                .register_merkle_root(merkle_root, Uint256::from_u128(200_000_u128), vec![])
+                .unwrap()
+        ).unwrap();
+
+        app.execute(
+            Addr::unchecked(ADMIN),
+            contract
+                .register_vesting(Uint256::from_u128(200_000_u128), vesting_start, vec![])
+                .unwrap()
+        ).unwrap();
+
+        assert_eq!(
+            contract.unallocated(&app.wrap()).unwrap().remaining_allocation,
+            Some(Uint256::zero())
+        );
+
+        let solvency = contract.check_solvency(&app.wrap()).unwrap();
+        assert_eq!(solvency.outstanding, Uint256::from_u128(880_000_u128));
+        assert!(solvency.solvent);
+    }
+
+    #[test]
+    fn early_unlock() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let msg = contract
+            .vest_tokens(
+                vec![UserVesting {
+                    amount: Uint256::from_u128(1_000_000_u128),
+                    user: USER.to_string(),
+                    extra: vec![],
+                }],
+                vec![]
+            )
+            .unwrap();
+
+        app.execute(Addr::unchecked(ADMIN), msg).unwrap();
+
+        let res = app.execute(
+            Addr::unchecked(USER),
+            contract.early_unlock(Uint256::from_u128(100_u128)).unwrap()
+        );
+        expect_error(res, "Early unlock is disabled".to_string());
+
+        let early_unlock = |destination: PenaltyDestination| {
+            contract
+                .set_early_unlock(
+                    Some(EarlyUnlockConfig {
+                        max_penalty_percents: Uint256::from_u128(5000_u128), // 50%
+                        destination,
+                    })
+                )
+                .unwrap()
+        };
+
+        let res = app.execute(Addr::unchecked(USER), early_unlock(PenaltyDestination::Burn));
+        expect_error(res, "Only admin".to_string());
+
+        app.execute(Addr::unchecked(ADMIN), early_unlock(PenaltyDestination::Treasury)).unwrap();
+
+        // second period is 7 minutes in, the 4 periods left unlock in 3, 8, 13 and 18 minutes
+        app.update_block(|block| {
+            block.time = block.time.plus_minutes(7);
+        });
+
+        let res = app.execute(
+            Addr::unchecked(USER),
+            contract.early_unlock(Uint256::from_u128(400_000_u128)).unwrap()
+        );
+        expect_error(res, "Treasury is not set".to_string());
+
+        let treasury = "treasury".to_string();
+
+        app.execute(
+            Addr::unchecked(ADMIN),
+            contract.change_treasury(Some(treasury.clone())).unwrap()
+        ).unwrap();
+
+        let res = app.execute(
+            Addr::unchecked(USER),
+            contract.early_unlock(Uint256::from_u128(680_001_u128)).unwrap()
+        );
+        expect_error(res, "Decrease exceeds locked amount".to_string());
+
+        // 50% scaled by 10.5 of 25 minutes left on average
+        let quote = contract
+            .quote_early_unlock(&app.wrap(), USER, Uint256::from_u128(400_000_u128))
+            .unwrap();
+
+        assert_eq!(quote.amount, Uint256::from_u128(400_000_u128));
+        assert_eq!(quote.penalty, Uint256::from_u128(84_000_u128));
+        assert_eq!(quote.released, Uint256::from_u128(316_000_u128));
+        assert_eq!(quote.destination, PenaltyDestination::Treasury);
+
+        let balance_before = app.wrap().query_balance(USER, INJEX_TOKEN).unwrap().amount;
+
+        app.execute(
+            Addr::unchecked(USER),
+            contract.early_unlock(Uint256::from_u128(400_000_u128)).unwrap()
+        ).unwrap();
+
+        assert_eq!(
+            app.wrap().query_balance(USER, INJEX_TOKEN).unwrap().amount - balance_before,
+            Uint128::new(316_000)
+        );
+        assert_eq!(
+            app.wrap().query_balance(treasury, INJEX_TOKEN).unwrap().amount,
+            Uint128::new(84_000)
+        );
+        assert_eq!(contract.total_vested(&app.wrap()).unwrap(), Uint256::from_u128(600_000_u128));
+
+        // unlocked periods are not touched
+        assert_eq!(
+            contract.claimable_amount(&app.wrap(), USER).unwrap(),
+            Uint256::from_u128(150_000_u128 + 170_000_u128)
+        );
+
+        app.execute(
+            Addr::unchecked(ADMIN),
+            early_unlock(PenaltyDestination::Burn)
+        ).unwrap();
+
+        let unallocated = contract.unallocated(&app.wrap()).unwrap().unallocated[0].amount;
+        let contract_balance = app
+            .wrap()
+            .query_balance(contract.addr(), INJEX_TOKEN)
+            .unwrap().amount;
+
+        app.execute(
+            Addr::unchecked(USER),
+            contract.early_unlock(Uint256::from_u128(40_000_u128)).unwrap()
+        ).unwrap();
+
+        // 31_600 released and 8_400 burnt, nothing is left behind in the pool
+        assert_eq!(
+            contract_balance -
+                app.wrap().query_balance(contract.addr(), INJEX_TOKEN).unwrap().amount,
+            Uint128::new(40_000)
+        );
+        assert_eq!(contract.unallocated(&app.wrap()).unwrap().unallocated[0].amount, unallocated);
+
+        app.update_block(|block| {
+            block.time = block.time.plus_minutes(20);
+        });
+
+        let balance_before = app.wrap().query_balance(USER, INJEX_TOKEN).unwrap().amount;
+
+        app.execute(Addr::unchecked(USER), contract.claim().unwrap()).unwrap();
+
+        assert_eq!(
+            app.wrap().query_balance(USER, INJEX_TOKEN).unwrap().amount - balance_before,
+            Uint128::new(150_000 + 170_000 + 4 * 60_000)
+        );
+
+        let res = app.execute(
+            Addr::unchecked(USER),
+            contract.early_unlock(Uint256::from_u128(1_u128)).unwrap()
+        );
+        expect_error(res, "Grant is fully vested".to_string());
+    }
+
+    #[test]
+    fn redistribute_forfeited() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let investor = "investor".to_string();
+
+        let msg = contract
+            .vest_tokens(
+                vec![
+                    UserVesting {
+                        amount: Uint256::from_u128(1_000_000_u128),
+                        user: USER.to_string(),
+                        extra: vec![],
+                    },
+                    UserVesting {
+                        amount: Uint256::from_u128(3_000_000_u128),
+                        user: ADMIN.to_string(),
+                        extra: vec![],
+                    },
+                    UserVesting {
+                        amount: Uint256::from_u128(2_000_000_u128),
+                        user: investor.clone(),
+                        extra: vec![],
+                    }
+                ],
+                vec![]
+            )
+            .unwrap();
+
+        app.execute(Addr::unchecked(ADMIN), msg).unwrap();
+
+        app.execute(
+            Addr::unchecked(ADMIN),
+            contract
+                .set_early_unlock(
+                    Some(EarlyUnlockConfig {
+                        max_penalty_percents: Uint256::from_u128(5000_u128), // 50%
+                        destination: PenaltyDestination::Redistribute,
+                    })
+                )
+                .unwrap()
+        ).unwrap();
+
+        app.update_block(|block| {
+            block.time = block.time.plus_minutes(7);
+        });
+
+        let unallocated = contract.unallocated(&app.wrap()).unwrap().unallocated[0].amount;
+
+        // 142_800 penalty shared over the 680_000 and 2_040_000 the others have locked, the
+        // 510_000 ADMIN has unlocked without claiming earn nothing
+        app.execute(
+            Addr::unchecked(&investor),
+            contract.early_unlock(Uint256::from_u128(680_000_u128)).unwrap()
+        ).unwrap();
+
+        assert_eq!(
+            contract.claimable_amount(&app.wrap(), USER).unwrap(),
+            Uint256::from_u128(150_000_u128 + 170_000_u128 + 35_700_u128)
+        );
+        assert_eq!(
+            contract.claimable_amount(&app.wrap(), &investor).unwrap(),
+            Uint256::from_u128(300_000_u128 + 340_000_u128)
+        );
+
+        let simulation = contract.simulate_at(&app.wrap(), USER, app.block_info().time).unwrap();
+        assert_eq!(simulation.bonus, Uint256::from_u128(35_700_u128));
+        assert_eq!(simulation.claimable, Uint256::from_u128(355_700_u128));
+
+        let balance_before = app.wrap().query_balance(USER, INJEX_TOKEN).unwrap().amount;
+
+        app.execute(Addr::unchecked(USER), contract.claim().unwrap()).unwrap();
+
+        assert_eq!(
+            app.wrap().query_balance(USER, INJEX_TOKEN).unwrap().amount - balance_before,
+            Uint128::new(355_700)
+        );
+
+        let history = contract.claim_history(&app.wrap(), USER, None, None).unwrap();
+        assert_eq!(history.claims[0].bonus_amount, Uint256::from_u128(35_700_u128));
+
+        // what is left locked for the investor goes to USER (680_000) and ADMIN (2_040_000)
+        app.wasm_sudo(
+            contract.addr(),
+            &(SudoMsg::RevokeGrant { user: investor.clone(), redistribute: true })
+        ).unwrap();
+
+        assert_eq!(
+            contract.claimable_amount(&app.wrap(), USER).unwrap(),
+            Uint256::from_u128(170_000_u128)
+        );
+        assert_eq!(
+            contract.claimable_amount(&app.wrap(), ADMIN).unwrap(),
+            Uint256::from_u128(450_000_u128 + 510_000_u128 + 107_100_u128 + 510_000_u128)
+        );
+        assert_eq!(
+            contract.claimable_amount(&app.wrap(), &investor).unwrap(),
+            Uint256::from_u128(300_000_u128 + 340_000_u128)
+        );
+
+        // shared tokens stay reserved for their beneficiaries
+        assert_eq!(contract.unallocated(&app.wrap()).unwrap().unallocated[0].amount, unallocated);
+        assert!(contract.check_solvency(&app.wrap()).unwrap().solvent);
+    }
+
+    #[test]
+    fn reward_distribution() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let vest = |user: &str, amount: u128| {
+            contract
+                .vest_tokens(
+                    vec![UserVesting {
+                        amount: Uint256::from_u128(amount),
+                        user: user.to_string(),
+                        extra: vec![],
+                    }],
+                    vec![]
+                )
+                .unwrap()
+        };
+
+        app.execute(Addr::unchecked(ADMIN), vest(USER, 1_000_000)).unwrap();
+        app.execute(Addr::unchecked(ADMIN), vest(ADMIN, 3_000_000)).unwrap();
+
+        let usdt = |amount: u128| {
+            vec![Coin {
+                denom: USDT.to_string(),
+                amount: Uint128::new(amount),
+            }]
+        };
+
+        let res = app.execute(
+            Addr::unchecked(ADMIN),
+            contract.distribute_rewards(usdt(34_000)).unwrap()
+        );
+        expect_error(res, "Invalid coin passed in funds".to_string());
+
+        let res = app.execute(
+            Addr::unchecked(USER),
+            contract.set_reward_denoms(vec![USDT.to_string()]).unwrap()
+        );
+        expect_error(res, "Only admin".to_string());
+
+        app.execute(
+            Addr::unchecked(ADMIN),
+            contract.set_reward_denoms(vec![USDT.to_string(), INJEX_TOKEN.to_string()]).unwrap()
+        ).unwrap();
+
+        // 850_000 and 2_550_000 are locked for USER and ADMIN
+        app.execute(
+            Addr::unchecked(ADMIN),
+            contract.distribute_rewards(usdt(34_000)).unwrap()
+        ).unwrap();
+
+        assert_eq!(contract.pending_rewards(&app.wrap(), USER).unwrap(), usdt(8_500));
+        assert_eq!(contract.pending_rewards(&app.wrap(), ADMIN).unwrap(), usdt(25_500));
+
+        // an unlock lowers the weight, rewards earned so far are kept
+        app.update_block(|block| {
+            block.time = block.time.plus_minutes(7);
+        });
+
+        app.execute(Addr::unchecked(USER), contract.claim().unwrap()).unwrap();
+
+        assert_eq!(contract.pending_rewards(&app.wrap(), USER).unwrap(), usdt(8_500));
+
+        // later beneficiaries only share what is distributed after they vested
+        app.execute(Addr::unchecked(ADMIN), vest("investor", 1_000_000)).unwrap();
+
+        // 680_000, 2_040_000 and 850_000 are locked now, unlocked tokens earn nothing whether
+        // they were claimed or not
+        app.execute(
+            Addr::unchecked(ADMIN),
+            contract.distribute_rewards(usdt(35_700)).unwrap()
+        ).unwrap();
+
+        assert_eq!(contract.pending_rewards(&app.wrap(), USER).unwrap(), usdt(8_500 + 6_800));
+        assert_eq!(contract.pending_rewards(&app.wrap(), ADMIN).unwrap(), usdt(25_500 + 20_400));
+        assert_eq!(contract.pending_rewards(&app.wrap(), "investor").unwrap(), usdt(8_500));
+
+        let balance_before = app.wrap().query_balance(USER, USDT).unwrap().amount;
+
+        app.execute(Addr::unchecked(USER), contract.claim_rewards(None).unwrap()).unwrap();
+
+        assert_eq!(
+            app.wrap().query_balance(USER, USDT).unwrap().amount - balance_before,
+            Uint128::new(15_300)
+        );
+        assert_eq!(contract.pending_rewards(&app.wrap(), USER).unwrap(), vec![]);
+
+        let res = app.execute(Addr::unchecked(USER), contract.claim_rewards(None).unwrap());
+        expect_error(res, "Cannot claim".to_string());
+
+        let rewards = contract.rewards(&app.wrap()).unwrap();
+
+        assert_eq!(rewards.total_weight, Uint256::from_u128(3_570_000_u128));
+        assert_eq!(rewards.rewards[0].total_distributed, Uint256::from_u128(69_700_u128));
+        assert_eq!(rewards.rewards[0].total_claimed, Uint256::from_u128(15_300_u128));
+
+        // positions untouched since they vested follow their unlocks as well, 510_000,
+        // 1_530_000 and 680_000 are locked after the next one
+        app.update_block(|block| {
+            block.time = block.time.plus_minutes(5);
+        });
+
+        app.execute(
+            Addr::unchecked(ADMIN),
+            contract.distribute_rewards(usdt(27_200)).unwrap()
+        ).unwrap();
+
+        assert_eq!(contract.pending_rewards(&app.wrap(), USER).unwrap(), usdt(5_100));
+        assert_eq!(
+            contract.pending_rewards(&app.wrap(), ADMIN).unwrap(),
+            usdt(25_500 + 20_400 + 15_300)
+        );
+        assert_eq!(
+            contract.pending_rewards(&app.wrap(), "investor").unwrap(),
+            usdt(8_500 + 6_800)
+        );
+
+        // rewards in the vesting denom are not part of the unallocated pool
+        let unallocated = contract.unallocated(&app.wrap()).unwrap().unallocated[0].amount;
+
+        app.execute(
+            Addr::unchecked(ADMIN),
+            contract
+                .distribute_rewards(
+                    vec![Coin {
+                        denom: INJEX_TOKEN.to_string(),
+                        amount: Uint128::new(40_800),
+                    }]
+                )
+                .unwrap()
+        ).unwrap();
+
+        assert_eq!(contract.unallocated(&app.wrap()).unwrap().unallocated[0].amount, unallocated);
+        assert!(contract.check_solvency(&app.wrap()).unwrap().solvent);
+    }
+
+    #[test]
+    fn timelocked_config_changes() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let code_id = app.store_code(contract_template());
+        let res = app.instantiate_contract(
+            code_id,
+            Addr::unchecked(ADMIN),
+            &(InstantiateMsg {
+                instant_claim_percents: Uint256::from_u128(1500_u128),
+                lock_minutes: Uint256::from_u128(5_u128),
+                lock_periods: Uint256::from_u128(5_u128),
+                injex_token: INJEX_TOKEN.to_string(),
+                admin: ADMIN.to_string(),
+                voucher_signer: None,
+                extra_denoms: vec![],
+                max_total_allocation: None,
+                treasury: None,
+                mint_cap: None,
+                early_unlock: None,
+                reward_denoms: vec![],
+                min_delay: 0,
+                approvals: None,
+                dao: None,
+            }),
+            &[],
+            "test",
+            None
+        );
+        expect_error(res.map(|_| AppResponse::default()), "Invalid min delay".to_string());
+
+        let now = app.block_info().time;
+
+        let res = app.execute(
+            Addr::unchecked(USER),
+            contract
+                .schedule_config_change(ConfigChange::MinDelay { new_min_delay: 3600 }, now)
+                .unwrap()
+        );
+        expect_error(res, "Only admin".to_string());
+
+        // the delay itself is timelocked and can't be turned off
+        let res = app.execute(
+            Addr::unchecked(ADMIN),
+            contract
+                .schedule_config_change(ConfigChange::MinDelay { new_min_delay: 1800 }, now)
+                .unwrap()
+        );
+        expect_error(res, "Effective time is earlier than the minimum delay".to_string());
+
+        let res = app.execute(
+            Addr::unchecked(ADMIN),
+            contract
+                .schedule_config_change(
+                    ConfigChange::MinDelay { new_min_delay: 0 },
+                    now.plus_seconds(3600)
+                )
+                .unwrap()
+        );
+        expect_error(res, "Invalid min delay".to_string());
+
+        app.execute(
+            Addr::unchecked(ADMIN),
+            contract
+                .schedule_config_change(
+                    ConfigChange::MinDelay { new_min_delay: 1800 },
+                    now.plus_seconds(3600)
+                )
+                .unwrap()
+        ).unwrap();
+        app.execute(Addr::unchecked(ADMIN), contract.cancel_config_change(1).unwrap()).unwrap();
+
+        assert_eq!(contract.config(&app.wrap()).unwrap().min_delay, 3600);
+
+        let res = app.execute(
+            Addr::unchecked(ADMIN),
+            contract.change_lock_minutes(Uint256::from_u128(10_u128)).unwrap()
+        );
+        expect_error(res, "Change has to be scheduled".to_string());
+
+        let lock_minutes = ConfigChange::LockMinutes {
+            new_lock_minutes: Uint256::from_u128(10_u128),
+        };
+
+        let res = app.execute(
+            Addr::unchecked(ADMIN),
+            contract.schedule_config_change(lock_minutes.clone(), now.plus_seconds(1800)).unwrap()
+        );
+        expect_error(res, "Effective time is earlier than the minimum delay".to_string());
+
+        let res = app.execute(
+            Addr::unchecked(ADMIN),
+            contract
+                .schedule_config_change(
+                    ConfigChange::InstantClaimPercents { new_percents: PERCENTS + Uint256::one() },
+                    now.plus_seconds(3600)
+                )
+                .unwrap()
+        );
+        expect_error(res, "Invalid percents".to_string());
+
+        app.execute(
+            Addr::unchecked(ADMIN),
+            contract.schedule_config_change(lock_minutes.clone(), now.plus_seconds(3600)).unwrap()
+        ).unwrap();
+        app.execute(
+            Addr::unchecked(ADMIN),
+            contract
+                .schedule_config_change(
+                    ConfigChange::InstantClaimPercents {
+                        new_percents: Uint256::from_u128(2000_u128),
+                    },
+                    now.plus_seconds(7200)
+                )
+                .unwrap()
+        ).unwrap();
+
+        let pending = contract.pending_config_changes(&app.wrap(), None, None).unwrap().changes;
+
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[0].id, 2);
+        assert_eq!(pending[0].change, lock_minutes);
+        assert_eq!(pending[0].effective_time, now.plus_seconds(3600));
+
+        let res = app.execute(Addr::unchecked(USER), contract.cancel_config_change(3).unwrap());
+        expect_error(res, "Only admin".to_string());
+
+        app.execute(Addr::unchecked(ADMIN), contract.cancel_config_change(3).unwrap()).unwrap();
+
+        let res = app.execute(Addr::unchecked(USER), contract.execute_config_change(3).unwrap());
+        expect_error(res, "Config change not found".to_string());
+
+        let res = app.execute(Addr::unchecked(USER), contract.execute_config_change(2).unwrap());
+        expect_error(res, "Config change is not effective yet".to_string());
+
+        app.update_block(|block| {
+            block.time = block.time.plus_seconds(3600);
+        });
+
+        app.execute(Addr::unchecked(USER), contract.execute_config_change(2).unwrap()).unwrap();
+
+        let config = contract.config(&app.wrap()).unwrap();
+
+        assert_eq!(config.lock_minutes, Uint256::from_u128(10_u128));
+        assert_eq!(config.instant_claim_percents, Uint256::from_u128(1500_u128));
+        assert_eq!(
+            contract.pending_config_changes(&app.wrap(), None, None).unwrap().changes,
+            vec![]
+        );
+    }
+
+    #[test]
+    fn approval_proposals() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let approver = "approver".to_string();
+        let stranger = "stranger".to_string();
+
+        app.execute(
+            Addr::unchecked(ADMIN),
+            contract
+                .set_approvals(
+                    Some(ApprovalsMsg {
+                        approvers: vec![ADMIN.to_string(), USER.to_string(), approver.clone()],
+                        threshold: 2,
+                        proposal_ttl: 3600,
+                    })
+                )
+                .unwrap()
+        ).unwrap();
+
+        let vest_msg = ExecuteMsg::VestTokens {
+            users: vec![UserVesting {
+                amount: Uint256::from_u128(1_000_000_u128),
+                user: USER.to_string(),
+                extra: vec![],
+            }],
+        };
+
+        let res = app.execute(Addr::unchecked(ADMIN), contract.call(vest_msg.clone()).unwrap());
+        expect_error(res, "Action has to be approved through a proposal".to_string());
+
+        let res = app.execute(
+            Addr::unchecked(&stranger),
+            contract.propose(vest_msg.clone()).unwrap()
+        );
+        expect_error(res, "Only approver".to_string());
+
+        let res = app.execute(
+            Addr::unchecked(ADMIN),
+            contract.propose(ExecuteMsg::Claim {}).unwrap()
+        );
+        expect_error(res, "Only privileged actions can be proposed".to_string());
+
+        app.execute(Addr::unchecked(ADMIN), contract.propose(vest_msg.clone()).unwrap()).unwrap();
+
+        let res = app.execute(Addr::unchecked(&stranger), contract.execute_proposal(1).unwrap());
+        expect_error(res, "Proposal has not reached the threshold".to_string());
+
+        let res = app.execute(Addr::unchecked(ADMIN), contract.approve(1).unwrap());
+        expect_error(res, "Already approved".to_string());
+
+        let open = contract.open_proposals(&app.wrap(), None, None).unwrap().proposals;
+
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].msg, vest_msg);
+        assert_eq!(open[0].approvals, vec![Addr::unchecked(ADMIN)]);
+
+        app.execute(Addr::unchecked(USER), contract.approve(1).unwrap()).unwrap();
+        app.execute(Addr::unchecked(&stranger), contract.execute_proposal(1).unwrap()).unwrap();
+
+        assert_eq!(contract.total_vested(&app.wrap()).unwrap(), Uint256::from_u128(1_000_000_u128));
+        assert!(contract.proposal(&app.wrap(), 1).unwrap().executed);
+        assert_eq!(contract.open_proposals(&app.wrap(), None, None).unwrap().proposals, vec![]);
+
+        let res = app.execute(Addr::unchecked(&stranger), contract.execute_proposal(1).unwrap());
+        expect_error(res, "Proposal was already executed".to_string());
+
+        // proposals carry no funds, the merkle root is allocated from the pool
+        let merkle_root = hex::encode(merkle_leaf(USER, 500_000, app.block_info().time));
+
+        app.execute(
+            Addr::unchecked(ADMIN),
+            contract
+                .propose(ExecuteMsg::RegisterMerkleRoot {
+                    merkle_root: merkle_root.clone(),
+                    total_amount: Uint256::from_u128(500_000_u128),
+                })
+                .unwrap()
+        ).unwrap();
+        app.execute(Addr::unchecked(&approver), contract.approve(2).unwrap()).unwrap();
+        app.execute(Addr::unchecked(&stranger), contract.execute_proposal(2).unwrap()).unwrap();
+
+        let allocation = contract.merkle_allocation(&app.wrap()).unwrap();
+        assert_eq!(allocation.merkle_root, merkle_root);
+        assert_eq!(allocation.total_amount, Uint256::from_u128(500_000_u128));
+
+        app.execute(
+            Addr::unchecked(USER),
+            contract
+                .propose(ExecuteMsg::SetMaxTotalAllocation {
+                    max_total_allocation: Some(Uint256::from_u128(10_000_000_u128)),
+                })
+                .unwrap()
+        ).unwrap();
+
+        app.update_block(|block| {
+            block.time = block.time.plus_seconds(3600);
+        });
+
+        let res = app.execute(Addr::unchecked(&approver), contract.approve(3).unwrap());
+        expect_error(res, "Proposal expired".to_string());
+
+        // turning approvals off needs approval as well
+        let res = app.execute(Addr::unchecked(ADMIN), contract.set_approvals(None).unwrap());
+        expect_error(res, "Action has to be approved through a proposal".to_string());
+
+        app.execute(
+            Addr::unchecked(&approver),
+            contract.propose(ExecuteMsg::SetApprovals { approvals: None }).unwrap()
+        ).unwrap();
+        app.execute(Addr::unchecked(ADMIN), contract.approve(4).unwrap()).unwrap();
+        app.execute(Addr::unchecked(ADMIN), contract.execute_proposal(4).unwrap()).unwrap();
+
+        assert_eq!(contract.config(&app.wrap()).unwrap().approvals, None);
+
+        let effective_time = app.block_info().time.plus_seconds(3600);
+
+        app.execute(
+            Addr::unchecked(ADMIN),
+            contract
+                .schedule_config_change(
+                    ConfigChange::LockMinutes { new_lock_minutes: Uint256::from_u128(10_u128) },
+                    effective_time
+                )
+                .unwrap()
+        ).unwrap();
+    }
+
+    #[test]
+    fn fully_claimed_counted_once() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        app.execute(
+            Addr::unchecked(ADMIN),
+            contract.set_extra_denoms(vec![USDT.to_string()]).unwrap()
+        ).unwrap();
+
+        let users = vec![UserVesting {
+            amount: Uint256::from_u128(1_000_000_u128),
+            user: USER.to_string(),
+            extra: vec![Coin {
+                denom: USDT.to_string(),
+                amount: Uint128::new(200_000),
+            }],
+        }];
+        let funds = vec![Coin {
+            denom: USDT.to_string(),
+            amount: Uint128::new(200_000),
+        }];
+
+        app.execute(Addr::unchecked(ADMIN), contract.vest_tokens(users, funds).unwrap()).unwrap();
+
+        app.update_block(|block| {
+            block.time = block.time.plus_minutes(7);
+        });
+
+        app.execute(Addr::unchecked(USER), contract.claim().unwrap()).unwrap();
+
+        // nothing is left in injex_token, the extras keep vesting
+        app.execute(
+            Addr::unchecked(ADMIN),
+            contract.decrease_grant(USER, 1, Uint256::from_u128(680_000_u128)).unwrap()
+        ).unwrap();
+
+        assert_eq!(contract.stats(&app.wrap()).unwrap().fully_claimed_count, 1);
+
+        app.update_block(|block| {
+            block.time = block.time.plus_minutes(5);
+        });
+
+        let usdt_before = app.wrap().query_balance(USER, USDT).unwrap().amount;
+
+        app.execute(Addr::unchecked(USER), contract.claim().unwrap()).unwrap();
+
+        assert_eq!(
+            app.wrap().query_balance(USER, USDT).unwrap().amount,
+            usdt_before + Uint128::new(34_000)
+        );
+        assert_eq!(contract.stats(&app.wrap()).unwrap().fully_claimed_count, 1);
+
+        app.update_block(|block| {
+            block.time = block.time.plus_minutes(30);
+        });
+
+        app.execute(Addr::unchecked(USER), contract.claim().unwrap()).unwrap();
+
+        assert_eq!(contract.stats(&app.wrap()).unwrap().fully_claimed_count, 1);
+    }
+
+    #[test]
+    fn revoke_grant_with_extras() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let treasury = "treasury".to_string();
+
+        app.execute(
+            Addr::unchecked(ADMIN),
+            contract.set_extra_denoms(vec![USDT.to_string()]).unwrap()
+        ).unwrap();
+        app.execute(
+            Addr::unchecked(ADMIN),
+            contract.change_treasury(Some(treasury.clone())).unwrap()
+        ).unwrap();
+
+        let users = [USER, ADMIN]
+            .iter()
+            .map(|user| -> UserVesting {
+                UserVesting {
+                    amount: Uint256::from_u128(1_000_000_u128),
+                    user: user.to_string(),
+                    extra: vec![Coin {
+                        denom: USDT.to_string(),
+                        amount: Uint128::new(200_000),
+                    }],
+                }
+            })
+            .collect();
+        let funds = vec![Coin {
+            denom: USDT.to_string(),
+            amount: Uint128::new(400_000),
+        }];
+
+        app.execute(Addr::unchecked(ADMIN), contract.vest_tokens(users, funds).unwrap()).unwrap();
+
+        app.update_block(|block| {
+            block.time = block.time.plus_minutes(7);
+        });
+
+        // the four locked USDT periods go to the other position as rewards
+        app.wasm_sudo(
+            contract.addr(),
+            &(SudoMsg::RevokeGrant { user: USER.to_string(), redistribute: true })
+        ).unwrap();
+
+        assert_eq!(
+            contract.pending_rewards(&app.wrap(), ADMIN).unwrap(),
+            vec![Coin {
+                denom: USDT.to_string(),
+                amount: Uint128::new(136_000),
+            }]
+        );
+        assert_eq!(
+            contract.extra_claimable(&app.wrap(), USER).unwrap(),
+            vec![Coin {
+                denom: USDT.to_string(),
+                amount: Uint128::new(64_000),
+            }]
+        );
+
+        // and are refunded to the treasury without redistribution
+        app.wasm_sudo(
+            contract.addr(),
+            &(SudoMsg::RevokeGrant { user: ADMIN.to_string(), redistribute: false })
+        ).unwrap();
+
+        assert_eq!(
+            app.wrap().query_balance(&treasury, USDT).unwrap().amount,
+            Uint128::new(136_000)
+        );
+
+        let usdt_before = app.wrap().query_balance(USER, USDT).unwrap().amount;
+
+        app.execute(Addr::unchecked(USER), contract.claim().unwrap()).unwrap();
+
+        assert_eq!(
+            app.wrap().query_balance(USER, USDT).unwrap().amount,
+            usdt_before + Uint128::new(64_000)
+        );
+
+        app.update_block(|block| {
+            block.time = block.time.plus_minutes(30);
+        });
+
+        let res = app.execute(Addr::unchecked(USER), contract.claim().unwrap());
+        expect_error(res, "Cannot claim".to_string());
+
+        let usdt_before = app.wrap().query_balance(ADMIN, USDT).unwrap().amount;
+
+        app.execute(Addr::unchecked(ADMIN), contract.claim_rewards(None).unwrap()).unwrap();
+
+        assert_eq!(
+            app.wrap().query_balance(ADMIN, USDT).unwrap().amount,
+            usdt_before + Uint128::new(136_000)
+        );
+
+        let totals = contract.totals(&app.wrap()).unwrap();
+
+        assert_eq!(totals.extra[0].total_vested, Uint256::from_u128(128_000_u128));
+    }
+
+    #[test]
+    fn vest_again_releases_replaced_grant() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let vest = |amount: u128| {
+            contract
+                .vest_tokens(
+                    vec![UserVesting {
+                        amount: Uint256::from_u128(amount),
+                        user: USER.to_string(),
+                        extra: vec![],
+                    }],
+                    vec![]
+                )
+                .unwrap()
+        };
+
+        app.execute(Addr::unchecked(ADMIN), vest(1_000_000)).unwrap();
+
+        app.update_block(|block| {
+            block.time = block.time.plus_minutes(7);
+        });
+
+        app.execute(Addr::unchecked(USER), contract.claim().unwrap()).unwrap();
+
+        let unallocated = contract.unallocated(&app.wrap()).unwrap().unallocated[0].amount;
+
+        // 680_000 of the first grant were still owed, only the new grant is now
+        app.execute(Addr::unchecked(ADMIN), vest(500_000)).unwrap();
+
+        assert_eq!(contract.total_vested(&app.wrap()).unwrap(), Uint256::from_u128(820_000_u128));
+        assert_eq!(
+            contract.check_solvency(&app.wrap()).unwrap().outstanding,
+            Uint256::from_u128(500_000_u128)
+        );
+        assert_eq!(
+            contract.unallocated(&app.wrap()).unwrap().unallocated[0].amount,
+            unallocated + Uint128::new(180_000)
+        );
+
+        let stats = contract.stats(&app.wrap()).unwrap();
+        assert_eq!(
+            stats.instant_liability + stats.unlocked_unclaimed + stats.locked,
+            Uint256::from_u128(500_000_u128)
+        );
+    }
+
+    #[test]
+    fn vest_again_releases_replaced_extras() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        app.execute(
+            Addr::unchecked(ADMIN),
+            contract.set_extra_denoms(vec![USDT.to_string()]).unwrap()
+        ).unwrap();
+
+        let vest = |amount: u128, extra: u128| {
+            contract
+                .vest_tokens(
+                    vec![UserVesting {
+                        amount: Uint256::from_u128(amount),
+                        user: USER.to_string(),
+                        extra: vec![Coin {
+                            denom: USDT.to_string(),
+                            amount: Uint128::new(extra),
+                        }],
+                    }],
+                    vec![Coin {
+                        denom: USDT.to_string(),
+                        amount: Uint128::new(extra),
+                    }]
+                )
+                .unwrap()
+        };
+
+        app.execute(Addr::unchecked(ADMIN), vest(1_000_000, 200_000)).unwrap();
+
+        app.update_block(|block| {
+            block.time = block.time.plus_minutes(7);
+        });
+
+        app.execute(Addr::unchecked(USER), contract.claim().unwrap()).unwrap();
+
+        let usdt_unallocated = |app: &InjectiveApp| -> Uint128 {
+            contract
+                .unallocated(&app.wrap())
+                .unwrap()
+                .unallocated.iter()
+                .find(|coin| coin.denom == USDT)
+                .map(|coin| coin.amount)
+                .unwrap_or_default()
+        };
+
+        let unallocated = usdt_unallocated(&app);
+
+        // 136_000 USDT of the first grant were still owed
+        app.execute(Addr::unchecked(ADMIN), vest(500_000, 100_000)).unwrap();
+
+        let totals = contract.totals(&app.wrap()).unwrap();
+
+        assert_eq!(totals.extra[0].total_vested, Uint256::from_u128(164_000_u128));
+        assert_eq!(totals.extra[0].total_claimed, Uint256::from_u128(64_000_u128));
+        assert_eq!(usdt_unallocated(&app), unallocated + Uint128::new(136_000));
+    }
+
+    #[test]
+    fn locked_total_follows_unlocks() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let vest = |user: &str, amount: u128| {
+            contract
+                .vest_tokens(
+                    vec![UserVesting {
+                        amount: Uint256::from_u128(amount),
+                        user: user.to_string(),
+                        extra: vec![],
+                    }],
+                    vec![]
+                )
+                .unwrap()
+        };
+
+        let total_weight = |app: &InjectiveApp| -> Uint256 {
+            contract.rewards(&app.wrap()).unwrap().total_weight
+        };
+
+        app.execute(
+            Addr::unchecked(ADMIN),
+            contract.set_reward_denoms(vec![USDT.to_string()]).unwrap()
+        ).unwrap();
+
+        app.execute(Addr::unchecked(ADMIN), vest(USER, 1_000_000)).unwrap();
+
+        app.update_block(|block| {
+            block.time = block.time.plus_minutes(3);
+        });
+
+        app.execute(Addr::unchecked(ADMIN), vest(ADMIN, 3_000_000)).unwrap();
+
+        assert_eq!(total_weight(&app), Uint256::from_u128(3_400_000_u128));
+
+        // the first unlock of USER is only released by the next execute, queries see it already
+        app.update_block(|block| {
+            block.time = block.time.plus_minutes(3);
+        });
+
+        assert_eq!(total_weight(&app), Uint256::from_u128(3_230_000_u128));
+
+        app.execute(
+            Addr::unchecked(ADMIN),
+            contract
+                .distribute_rewards(
+                    vec![Coin {
+                        denom: USDT.to_string(),
+                        amount: Uint128::new(32_300),
+                    }]
+                )
+                .unwrap()
+        ).unwrap();
+
+        assert_eq!(
+            contract.pending_rewards(&app.wrap(), ADMIN).unwrap()[0].amount,
+            Uint128::new(25_500)
+        );
+
+        // everything has unlocked by the time a longer lock_minutes applies, which locks the
+        // periods it moves past the current time again
+        app.execute(
+            Addr::unchecked(ADMIN),
+            contract
+                .schedule_config_change(
+                    ConfigChange::LockMinutes {
+                        new_lock_minutes: Uint256::from_u128(60_u128),
+                    },
+                    app.block_info().time.plus_seconds(3600)
+                )
+                .unwrap()
+        ).unwrap();
+
+        app.update_block(|block| {
+            block.time = block.time.plus_minutes(60);
+        });
+
+        assert_eq!(total_weight(&app), Uint256::zero());
+
+        let id = contract.pending_config_changes(&app.wrap(), None, None).unwrap().changes[0].id;
+
+        app.execute(Addr::unchecked(USER), contract.execute_config_change(id).unwrap()).unwrap();
+
+        assert_eq!(total_weight(&app), Uint256::from_u128(2_720_000_u128));
+
+        app.update_block(|block| {
+            block.time = block.time.plus_minutes(60);
+        });
+
+        assert_eq!(total_weight(&app), Uint256::from_u128(2_040_000_u128));
+    }
+
+    #[test]
+    fn tokenized_grants_have_no_voting_power() {
+        let (mut app, contract) = proper_instantiate(true);
+
+        let nft_code_id = app.store_code(grant_nft_template());
+
+        let nft_contract = app
+            .instantiate_contract(
+                nft_code_id,
+                Addr::unchecked(ADMIN),
+                &(cw721_base::InstantiateMsg {
+                    name: "Injex Vesting Grants".to_string(),
+                    symbol: "INJXG".to_string(),
+                    minter: contract.addr().to_string(),
+                }),
+                &[],
+                "grants",
+                None
+            )
+            .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract.addr(),
+            &(ExecuteMsg::SetGrantNft { nft_contract: nft_contract.to_string() }),
+            &[]
+        ).unwrap();
+
+        app.execute(
+            Addr::unchecked(ADMIN),
+            contract
+                .vest_tokens(
+                    vec![UserVesting {
+                        amount: Uint256::from_u128(1_000_000_u128),
+                        user: USER.to_string(),
+                        extra: vec![],
+                    }],
+                    vec![]
+                )
+                .unwrap()
+        ).unwrap();
+
+        // the grant is sold, neither side keeps or gains power
+        app.execute_contract(
+            Addr::unchecked(USER),
+            nft_contract,
+            &(cw721_base::ExecuteMsg::<Option<Empty>, Empty>::TransferNft {
+                recipient: ADMIN.to_string(),
+                token_id: "1".to_string(),
+            }),
+            &[]
+        ).unwrap();
+
+        app.update_block(|block| {
+            block.height += 1;
+        });
+
+        for address in [USER, ADMIN] {
+            let power: VotingPowerAtHeightResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    contract.addr(),
+                    &(QueryMsg::VotingPowerAtHeight { address: address.to_string(), height: None })
+                )
+                .unwrap();
+
+            assert_eq!(power.power, Uint128::zero());
+        }
+
+        let total_power: TotalPowerAtHeightResponse = app
+            .wrap()
+            .query_wasm_smart(contract.addr(), &(QueryMsg::TotalPowerAtHeight { height: None }))
+            .unwrap();
+
+        assert_eq!(total_power.power, Uint128::zero());
+    }
 }